@@ -24,9 +24,11 @@ pub use tupleobject::*;
 pub use listobject::*;
 pub use dictobject::*;
 pub use enumobject::*;
+pub use iterobject::*;
 pub use setobject::*;
 pub use pyerrors::*;
 pub use pystate::*;
+pub use pythread::*;
 pub use pystate::PyGILState_STATE::*;
 pub use methodobject::*;
 pub use moduleobject::*;
@@ -35,6 +37,7 @@ pub use classobject::*;
 pub use fileobject::*;
 pub use cobject::*;
 pub use pycapsule::*;
+pub use traceback::*;
 
 pub use descrobject::*;
 pub use warnings::*;
@@ -42,6 +45,7 @@ pub use pyarena::*;
 pub use modsupport::*;
 pub use pythonrun::*;
 pub use ceval::*;
+pub use sysmodule::*;
 pub use import::*;
 pub use objectabstract::*;
 pub use code::*;
@@ -70,6 +74,7 @@ mod tupleobject;
 mod listobject;
 mod dictobject;
 mod enumobject;
+mod iterobject;
 mod setobject;
 mod methodobject;
 mod moduleobject;
@@ -78,10 +83,9 @@ mod classobject;
 mod fileobject;
 mod cobject;
 mod pycapsule;
-// mod traceback; // TODO: incomplete
+mod traceback;
 // mod sliceobject; // TODO: incomplete
 // mod cellobject; // TODO: incomplete
-// mod iterobject; // TODO: incomplete
 // mod genobject; // TODO: incomplete
 mod descrobject; // TODO: incomplete
 mod warnings;
@@ -91,12 +95,13 @@ mod warnings;
 mod pyerrors;
 
 mod pystate;
+mod pythread;
 
 mod pyarena;
 mod modsupport;
 mod pythonrun;
 mod ceval;
-// mod sysmodule; // TODO: incomplete
+mod sysmodule;
 // mod intrcheck; // TODO: incomplete
 mod import;
 
@@ -107,4 +107,9 @@ pub unsafe fn PyThreadState_GET() -> *mut PyThreadState {
     _PyThreadState_Current
 }
 
+#[inline(always)]
+pub unsafe fn PyThreadState_GetInterpreter(tstate: *mut PyThreadState) -> *mut PyInterpreterState {
+    (*tstate).interp
+}
+
 
@@ -12,10 +12,14 @@ pub const PyCF_SOURCE_IS_UTF8 : c_int = 0x0100;
 pub const PyCF_DONT_IMPLY_DEDENT : c_int = 0x0200;
 pub const PyCF_ONLY_AST : c_int = 0x0400;
 
+pub const Py_single_input : c_int = 256;
+pub const Py_file_input : c_int = 257;
+pub const Py_eval_input : c_int = 258;
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct PyCompilerFlags {
-    cf_flags : c_int
+    pub cf_flags : c_int
 }
 
 #[allow(missing_copy_implementations)]
@@ -119,3 +123,7 @@ extern "C" {
     fn _Py_hgversion() -> *const c_char;
 }
 
+#[inline]
+pub unsafe fn Py_CompileString(string: *const c_char, p: *const c_char, s: c_int) -> *mut PyObject {
+    Py_CompileStringFlags(string, p, s, ::std::ptr::null_mut())
+}
@@ -0,0 +1,6 @@
+use libc::c_long;
+
+extern "C" {
+    pub fn PyThread_get_thread_ident() -> c_long;
+}
+
@@ -42,6 +42,7 @@ pub use codecs::*;
 pub use pyerrors::*;
 
 pub use pystate::*;
+pub use pythread::*;
 
 pub use modsupport::*;
 pub use pythonrun::*;
@@ -109,6 +110,7 @@ mod codecs;
 mod pyerrors;
 
 mod pystate;
+mod pythread;
 
 // mod pyarena; excluded by PEP-384
 mod modsupport;
@@ -46,3 +46,18 @@ pub unsafe fn PyThreadState_GET() -> *mut PyThreadState {
     PyThreadState_Get()
 }
 
+// Leading fields of the (otherwise opaque) PyThreadState struct, as of Python 3.4.
+#[repr(C)]
+struct PyThreadStateHead {
+    prev: *mut PyThreadState,
+    next: *mut PyThreadState,
+    interp: *mut PyInterpreterState
+}
+
+/// Gets the interpreter the thread state belongs to.
+/// Note: this is not part of the stable ABI, it relies on the struct layout.
+#[inline]
+pub unsafe fn PyThreadState_GetInterpreter(tstate: *mut PyThreadState) -> *mut PyInterpreterState {
+    (*(tstate as *mut PyThreadStateHead)).interp
+}
+
@@ -15,6 +15,10 @@ extern "C" {
     pub fn Py_EndInterpreter(arg1: *mut PyThreadState) -> ();
 }
 
+pub const Py_single_input : c_int = 256;
+pub const Py_file_input : c_int = 257;
+pub const Py_eval_input : c_int = 258;
+
 pub enum symtable {}
 pub enum _node {}
 
@@ -17,14 +17,16 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std;
-use python::{PythonObject, Python, ToPythonPointer, PythonObjectDowncastError};
-use objects::{PyObject, PyType, exc};
+use python::{PythonObject, Python, ToPythonPointer, PythonObjectDowncastError, PythonObjectWithTypeObject};
+use objects::{PyObject, PyType, PyTuple, PyTraceback, exc};
 #[cfg(feature="python27-sys")]
 use objects::oldstyle::PyClass;
 use ffi;
 use libc;
 use conversion::ToPyObject;
-use std::ffi::{CString, CStr};
+use std::ffi::{CString, CStr, NulError};
+use std::io;
+use std::num::{ParseIntError, ParseFloatError};
 
 /// Represents a python exception that was raised.
 #[derive(Clone, Debug)]
@@ -80,12 +82,28 @@ impl <'p> PyErr<'p> {
         }
     }
     
-    /// Creates a new PyErr.
+    /// Creates a new PyErr of type `T`.
+    ///
+    /// `value` can be:
+    /// * a tuple: the exception instance will be created using python `T(*tuple)`
+    /// * any other value: the exception instance will be created using python `T(value)`
+    ///
+    /// The exception instance is created lazily, when it is first needed.
+    ///
+    /// Example:
+    /// `return Err(PyErr::new::<exc::TypeError, _>(py, "Error message"));`
+    pub fn new<T, V>(py: Python<'p>, value: V) -> PyErr<'p>
+        where T: PythonObjectWithTypeObject<'p>, V: ToPyObject<'p>
+    {
+        PyErr::new_lazy_init(py.get_type::<T>(), Some(value.into_py_object(py).into_object()))
+    }
+
+    /// Creates a new PyErr from an exception instance or type.
     ///
     /// If `obj` is a python exception instance, the PyErr will use that instance.
     /// If `obj` is a python exception type, the PyErr will (lazily) create a new instance of that type
     /// Otherwise, a `TypeError` is returned instead.
-    pub fn new<O>(obj: O) -> PyErr<'p> where O: PythonObject<'p> {
+    pub fn from_instance<O>(obj: O) -> PyErr<'p> where O: PythonObject<'p> {
         PyErr::new_from_object(obj.into_object())
     }
     
@@ -124,6 +142,293 @@ impl <'p> PyErr<'p> {
         }
     }
 
+    /// Creates a new exception type with the given name and docstring.
+    ///
+    /// `name` must be of the form `module.ExceptionName`.
+    /// Returns `ValueError` if `name` or `doc` contain NUL characters.
+    /// If `base` is `None`, the new type derives from `Exception`.
+    pub fn new_type(py: Python<'p>, name: &str, doc: Option<&str>, base: Option<PyType<'p>>) -> PyResult<'p, PyType<'p>> {
+        let name = try!(CString::new(name).map_err(|e| e.to_py_err(py)));
+        let doc = match doc {
+            Some(doc) => Some(try!(CString::new(doc).map_err(|e| e.to_py_err(py)))),
+            None => None
+        };
+        unsafe {
+            result_cast_from_owned_ptr(py,
+                ffi::PyErr_NewExceptionWithDoc(name.as_ptr() as *mut _,
+                    doc.as_ref().map_or(std::ptr::null(), |doc| doc.as_ptr()) as *mut _,
+                    base.as_ptr(), std::ptr::null_mut()))
+        }
+    }
+
+    /// Prefixes the message of a conversion error (`TypeError`, `ValueError` or `OverflowError`)
+    /// with `context`, e.g. turning `expected int, got str` into `argument 'n': expected int, got str`.
+    /// Other errors are returned unchanged.
+    pub fn with_context(mut self, context: &str) -> PyErr<'p> {
+        let py = self.ptype.python();
+        let is_conversion_error = self.ptype == py.get_type::<exc::TypeError>().into_object()
+            || self.ptype == py.get_type::<exc::ValueError>().into_object()
+            || self.ptype == py.get_type::<exc::OverflowError>().into_object();
+        if !is_conversion_error {
+            return self;
+        }
+        let msg = match self.message() {
+            Some(msg) => msg,
+            None => return self
+        };
+        let msg = if msg.is_empty() { context.to_owned() } else { format!("{}: {}", context, msg) };
+        self.with_message(msg)
+    }
+
+    /// Gets `str(instance)`, or `None` if the conversion fails.
+    fn message(&mut self) -> Option<String> {
+        use objectprotocol::ObjectProtocol;
+        match self.instance().str() {
+            Ok(msg) => Some(::objects::PyString::extract_lossy(&msg).map(|msg| msg.into_owned()).unwrap_or(String::new())),
+            Err(_) => None
+        }
+    }
+
+    /// Replaces the exception value with the message `msg`, keeping type and traceback.
+    fn with_message(self, msg: String) -> PyErr<'p> {
+        let py = self.ptype.python();
+        PyErr {
+            ptype: self.ptype,
+            pvalue: Some(msg.to_py_object(py).into_object()),
+            ptraceback: self.ptraceback
+        }
+    }
+
+    /// Sets `cause` as the direct cause of this error,
+    /// like the python statement `raise self from cause`.
+    ///
+    /// Python 2 has no exception chaining; there the cause is only stored
+    /// in the `__cause__` attribute and is not shown in tracebacks.
+    #[cfg(feature="python3-sys")]
+    pub fn with_cause(mut self, cause: PyErr<'p>) -> PyErr<'p> {
+        let cause = cause.into_instance_with_traceback();
+        unsafe { ffi::PyException_SetCause(self.instance().as_ptr(), cause.steal_ptr()) };
+        self
+    }
+
+    /// Sets `cause` as the direct cause of this error,
+    /// like the python statement `raise self from cause`.
+    ///
+    /// Python 2 has no exception chaining; there the cause is stored
+    /// in the `__cause__` attribute and appended to the message of this error,
+    /// e.g. `RuntimeError: outer (caused by KeyError: 'inner')`.
+    #[cfg(feature="python27-sys")]
+    pub fn with_cause(self, cause: PyErr<'p>) -> PyErr<'p> {
+        use objectprotocol::ObjectProtocol;
+        let mut err = self.with_chained_message("caused by", &cause);
+        let cause = cause.into_instance_with_traceback();
+        let instance = err.instance();
+        let _ = instance.setattr("__cause__", &cause);
+        let _ = instance.setattr("__suppress_context__", &true);
+        err
+    }
+
+    /// Sets `context` as the exception that was being handled when this error was raised.
+    ///
+    /// Python 2 has no exception chaining; there the context is only stored
+    /// in the `__context__` attribute and is not shown in tracebacks.
+    #[cfg(feature="python3-sys")]
+    pub fn with_exception_context(mut self, context: PyErr<'p>) -> PyErr<'p> {
+        let context = context.into_instance_with_traceback();
+        let instance = self.instance();
+        // Avoid creating a cycle when re-raising the same exception.
+        if context != instance {
+            unsafe { ffi::PyException_SetContext(instance.as_ptr(), context.steal_ptr()) };
+        }
+        self
+    }
+
+    /// Sets `context` as the exception that was being handled when this error was raised.
+    ///
+    /// Python 2 has no exception chaining; there the context is stored
+    /// in the `__context__` attribute and, unless a cause was set,
+    /// appended to the message of this error,
+    /// e.g. `ValueError: second (raised while handling KeyError: 'first')`.
+    #[cfg(feature="python27-sys")]
+    pub fn with_exception_context(mut self, mut context: PyErr<'p>) -> PyErr<'p> {
+        use objectprotocol::ObjectProtocol;
+        // Avoid creating a cycle when re-raising the same exception.
+        if context.instance() == self.instance() {
+            return self;
+        }
+        let mut err = if self.suppress_context() {
+            self
+        } else {
+            self.with_chained_message("raised while handling", &context)
+        };
+        let context = context.into_instance_with_traceback();
+        let _ = err.instance().setattr("__context__", &context);
+        err
+    }
+
+    /// Appends `(<relation> <other>)` to the message of this error.
+    /// Returns the error unchanged if the exception type
+    /// cannot be constructed from the new message.
+    #[cfg(feature="python27-sys")]
+    fn with_chained_message(mut self, relation: &str, other: &PyErr<'p>) -> PyErr<'p> {
+        use objectprotocol::ObjectProtocol;
+        let msg = match self.message() {
+            Some(ref msg) if msg.is_empty() => format!("({} {})", relation, other),
+            Some(msg) => format!("{} ({} {})", msg, relation, other),
+            None => return self
+        };
+        match self.ptype.call(&(msg,), None) {
+            Ok(instance) => PyErr {
+                ptype: self.ptype,
+                pvalue: Some(instance),
+                ptraceback: self.ptraceback
+            },
+            Err(_) => self
+        }
+    }
+
+    /// Gets the exception that is currently being handled, as returned by `sys.exc_info()`.
+    #[cfg(feature="python3-sys")]
+    fn handled_exception(py: Python<'p>) -> Option<PyErr<'p>> {
+        let mut ptype : *mut ffi::PyObject = std::ptr::null_mut();
+        let mut pvalue : *mut ffi::PyObject = std::ptr::null_mut();
+        let mut ptraceback : *mut ffi::PyObject = std::ptr::null_mut();
+        unsafe {
+            ffi::PyErr_GetExcInfo(&mut ptype, &mut pvalue, &mut ptraceback);
+            PyErr::from_exc_info(py,
+                PyObject::from_owned_ptr_opt(py, ptype),
+                PyObject::from_owned_ptr_opt(py, pvalue),
+                PyObject::from_owned_ptr_opt(py, ptraceback))
+        }
+    }
+
+    /// Gets the exception that is currently being handled, as returned by `sys.exc_info()`.
+    #[cfg(feature="python27-sys")]
+    fn handled_exception(py: Python<'p>) -> Option<PyErr<'p>> {
+        unsafe {
+            let tstate = ffi::PyThreadState_Get();
+            PyErr::from_exc_info(py,
+                PyObject::from_borrowed_ptr_opt(py, (*tstate).exc_type),
+                PyObject::from_borrowed_ptr_opt(py, (*tstate).exc_value),
+                PyObject::from_borrowed_ptr_opt(py, (*tstate).exc_traceback))
+        }
+    }
+
+    fn from_exc_info(py: Python<'p>, ptype: Option<PyObject<'p>>, pvalue: Option<PyObject<'p>>, ptraceback: Option<PyObject<'p>>) -> Option<PyErr<'p>> {
+        match ptype {
+            Some(ptype) => {
+                if ptype == py.None() {
+                    return None;
+                }
+                Some(PyErr {
+                    ptype: ptype,
+                    pvalue: pvalue.and_then(|v| if v == py.None() { None } else { Some(v) }),
+                    ptraceback: ptraceback.and_then(|tb| if tb == py.None() { None } else { Some(tb) })
+                })
+            }
+            None => None
+        }
+    }
+
+    /// Gets the exception instance, with the traceback attached as `__traceback__`.
+    #[cfg(feature="python3-sys")]
+    fn into_instance_with_traceback(mut self) -> PyObject<'p> {
+        let instance = self.instance();
+        if let Some(ref tb) = self.ptraceback {
+            unsafe { ffi::PyException_SetTraceback(instance.as_ptr(), tb.as_ptr()) };
+        }
+        instance
+    }
+
+    /// Gets the exception instance, with the traceback attached as `__traceback__`.
+    #[cfg(feature="python27-sys")]
+    fn into_instance_with_traceback(mut self) -> PyObject<'p> {
+        use objectprotocol::ObjectProtocol;
+        let instance = self.instance();
+        if let Some(ref tb) = self.ptraceback {
+            let _ = instance.setattr("__traceback__", tb);
+        }
+        instance
+    }
+
+    /// Creates a PyErr from an exception instance, using its `__traceback__`.
+    #[cfg(feature="python3-sys")]
+    fn from_instance_with_traceback(instance: PyObject<'p>) -> PyErr<'p> {
+        let py = instance.python();
+        let mut err = PyErr::from_instance(instance);
+        if let Some(ref instance) = err.pvalue {
+            err.ptraceback = unsafe { PyObject::from_owned_ptr_opt(py, ffi::PyException_GetTraceback(instance.as_ptr())) };
+        }
+        err
+    }
+
+    /// Creates a PyErr from an exception instance, using its `__traceback__`.
+    #[cfg(feature="python27-sys")]
+    fn from_instance_with_traceback(instance: PyObject<'p>) -> PyErr<'p> {
+        use objectprotocol::ObjectProtocol;
+        let traceback = instance.getattr("__traceback__").ok();
+        let mut err = PyErr::from_instance(instance);
+        err.ptraceback = traceback;
+        err
+    }
+
+    /// Gets the exception stored in the attribute `name` of the instance,
+    /// or `None` if the attribute does not exist or is `None`.
+    #[cfg(feature="python27-sys")]
+    fn chained_exception(&mut self, name: &str) -> Option<PyErr<'p>> {
+        use objectprotocol::ObjectProtocol;
+        let py = self.ptype.python();
+        match self.instance().getattr(name) {
+            Ok(ref value) if *value == py.None() => None,
+            Ok(value) => Some(PyErr::from_instance_with_traceback(value)),
+            Err(_) => None
+        }
+    }
+
+    /// Gets the direct cause of this error (`__cause__`).
+    #[cfg(feature="python3-sys")]
+    pub fn cause(&mut self) -> Option<PyErr<'p>> {
+        let py = self.ptype.python();
+        let instance = self.instance();
+        unsafe { PyObject::from_owned_ptr_opt(py, ffi::PyException_GetCause(instance.as_ptr())) }
+            .map(PyErr::from_instance_with_traceback)
+    }
+
+    /// Gets the direct cause of this error (`__cause__`).
+    #[cfg(feature="python27-sys")]
+    pub fn cause(&mut self) -> Option<PyErr<'p>> {
+        self.chained_exception("__cause__")
+    }
+
+    /// Gets the exception that was being handled when this error was raised (`__context__`).
+    #[cfg(feature="python3-sys")]
+    pub fn context(&mut self) -> Option<PyErr<'p>> {
+        let py = self.ptype.python();
+        let instance = self.instance();
+        unsafe { PyObject::from_owned_ptr_opt(py, ffi::PyException_GetContext(instance.as_ptr())) }
+            .map(PyErr::from_instance_with_traceback)
+    }
+
+    /// Gets the exception that was being handled when this error was raised (`__context__`).
+    #[cfg(feature="python27-sys")]
+    pub fn context(&mut self) -> Option<PyErr<'p>> {
+        self.chained_exception("__context__")
+    }
+
+    /// Gets whether the context is hidden in tracebacks (`__suppress_context__`).
+    /// This is set when a cause is assigned.
+    pub fn suppress_context(&mut self) -> bool {
+        use objectprotocol::ObjectProtocol;
+        self.instance().getattr("__suppress_context__")
+            .and_then(|v| v.extract::<bool>()).unwrap_or(false)
+    }
+
+    /// Sets `__suppress_context__`.
+    pub fn set_suppress_context(&mut self, suppress: bool) {
+        use objectprotocol::ObjectProtocol;
+        let _ = self.instance().setattr("__suppress_context__", &suppress);
+    }
+
     /// Print a standard traceback to sys.stderr.
     pub fn print(self) {
         self.restore();
@@ -193,6 +498,35 @@ impl <'p> PyErr<'p> {
         }
     }
 
+    /// Gets the arguments the exception instance was constructed with (`args`).
+    /// For specific exception payloads, cast the instance to the exception type,
+    /// e.g. `err.instance().cast_into::<exc::OSError>()`, and use its accessors.
+    pub fn args(&mut self) -> PyTuple<'p> {
+        let py = self.ptype.python();
+        match self.instance().cast_into::<exc::BaseException>() {
+            Ok(e) => e.args(),
+            Err(_) => PyTuple::new(py, &[])
+        }
+    }
+
+    /// Retrieves the traceback associated with this error, if any.
+    pub fn traceback(&self) -> Option<PyTraceback<'p>> {
+        self.ptraceback.as_ref().and_then(|tb| tb.clone().cast_into::<PyTraceback>().ok())
+    }
+
+    /// Formats the error like python's `traceback.format_exception()`:
+    /// the traceback followed by the exception type and message.
+    pub fn format(&mut self) -> PyResult<'p, String> {
+        use objectprotocol::ObjectProtocol;
+        let py = self.ptype.python();
+        let instance = self.instance();
+        let traceback = try!(py.import("traceback"));
+        let tb = self.ptraceback.clone().unwrap_or(py.None());
+        let lines = try!(traceback.as_object().call_method("format_exception",
+            &(self.ptype.clone(), instance, tb), None));
+        join_lines(&lines)
+    }
+
     /// Retrieves the exception instance for this error.
     /// This method takes `&mut self` because the error might need
     /// to be normalized in order to create the exception instance.
@@ -206,8 +540,17 @@ impl <'p> PyErr<'p> {
 
     /// Writes the error back to the python interpreter's global state.
     /// This is the opposite of `PyErr::fetch()`.
-    #[inline]
-    pub fn restore(self) {
+    ///
+    /// If an exception is currently being handled (see `sys.exc_info()`),
+    /// it is attached as `__context__` unless the error already has a context,
+    /// like python does when raising an exception within an `except` block.
+    pub fn restore(mut self) {
+        let py = self.ptype.python();
+        if let Some(handled) = PyErr::handled_exception(py) {
+            if self.context().is_none() {
+                self = self.with_exception_context(handled);
+            }
+        }
         let PyErr { ptype, pvalue, ptraceback } = self;
         unsafe {
             ffi::PyErr_Restore(ptype.steal_ptr(), pvalue.steal_ptr(), ptraceback.steal_ptr())
@@ -216,18 +559,171 @@ impl <'p> PyErr<'p> {
     
     /// Issue a warning message.
     /// May return a PyErr if warnings-as-errors is enabled.
+    ///
+    /// `category` is usually one of the warning types in the `exc` module,
+    /// e.g. `py.get_type::<exc::DeprecationWarning>()`, or a custom category defined
+    /// with `py_exception!`.
     pub fn warn(py: Python<'p>, category: &PyObject, message: &str, stacklevel: i32) -> PyResult<'p, ()> {
-        let message = CString::new(message).unwrap();
+        let message = try!(CString::new(message).map_err(|e| e.to_py_err(py)));
         unsafe {
             error_on_minusone(py, ffi::PyErr_WarnEx(category.as_ptr(), message.as_ptr(), stacklevel as ffi::Py_ssize_t))
         }
     }
+
+    /// Issue a warning message attributed to the given source location,
+    /// like python's `warnings.warn_explicit()`.
+    /// If `module` is `None`, the module name is derived from `filename`.
+    /// May return a PyErr if warnings-as-errors is enabled.
+    pub fn warn_explicit(py: Python<'p>, category: &PyObject, message: &str,
+                         filename: &str, lineno: i32, module: Option<&str>) -> PyResult<'p, ()> {
+        let message = try!(CString::new(message).map_err(|e| e.to_py_err(py)));
+        let filename = try!(CString::new(filename).map_err(|e| e.to_py_err(py)));
+        let module = match module {
+            Some(module) => Some(try!(CString::new(module).map_err(|e| e.to_py_err(py)))),
+            None => None
+        };
+        unsafe {
+            error_on_minusone(py, ffi::PyErr_WarnExplicit(category.as_ptr(), message.as_ptr(),
+                filename.as_ptr(), lineno as libc::c_int,
+                module.as_ref().map_or(std::ptr::null(), |m| m.as_ptr()), std::ptr::null_mut()))
+        }
+    }
+}
+
+/// Formats the error as `TypeName: message`, like the last line of a python traceback.
+impl <'p> std::fmt::Display for PyErr<'p> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use objectprotocol::ObjectProtocol;
+        let mut err = self.clone();
+        let type_name = match err.ptype.getattr("__name__") {
+            Ok(name) => ::objects::PyString::extract_lossy(&name).map(|s| s.into_owned()).unwrap_or(String::new()),
+            Err(_) => String::new()
+        };
+        let msg = match err.instance().str() {
+            Ok(msg) => ::objects::PyString::extract_lossy(&msg).map(|s| s.into_owned()).unwrap_or(String::new()),
+            Err(_) => String::new()
+        };
+        if msg.is_empty() {
+            f.write_str(&type_name)
+        } else {
+            write!(f, "{}: {}", type_name, msg)
+        }
+    }
+}
+
+impl <'p> std::error::Error for PyErr<'p> {
+    fn description(&self) -> &str {
+        "python exception"
+    }
 }
 
 /// Converts `PythonObjectDowncastError` to python `TypeError`.
 impl <'p> std::convert::From<PythonObjectDowncastError<'p>> for PyErr<'p> {
     fn from(err: PythonObjectDowncastError<'p>) -> PyErr<'p> {
-        PyErr::new_lazy_init(err.0.get_type::<exc::TypeError>(), None)
+        let py = err.python();
+        PyErr::new::<exc::TypeError, _>(py, err.to_string())
+    }
+}
+
+/// Describes how a Rust error type is converted into a python exception.
+///
+/// Implement this trait for the error types of a library to declare
+/// which python exception they map to. In functions returning `PyResult`,
+/// `PyResultExt::py_err()` converts the errors so that `try!` can be used:
+/// `let n = try!(s.parse::<i32>().py_err(py));`.
+pub trait ToPyErr : std::fmt::Display {
+    /// Gets the python exception type for this error.
+    fn exception_type<'p>(&self, py: Python<'p>) -> PyType<'p>;
+
+    /// Gets the value passed to the exception constructor;
+    /// a tuple is used as the argument list.
+    /// The default implementation uses the `Display` output as message.
+    fn exception_value<'p>(&self, py: Python<'p>) -> PyObject<'p> {
+        self.to_string().to_py_object(py).into_object()
+    }
+
+    /// Creates a `PyErr` for this error.
+    fn to_py_err<'p>(&self, py: Python<'p>) -> PyErr<'p> {
+        PyErr::new_lazy_init(self.exception_type(py), Some(self.exception_value(py)))
+    }
+}
+
+/// Converts a Rust error into a python exception.
+/// The GIL token is required to create the exception: `PyErr::from((py, e))`.
+impl <'p, E> std::convert::From<(Python<'p>, E)> for PyErr<'p> where E: ToPyErr {
+    fn from((py, err): (Python<'p>, E)) -> PyErr<'p> {
+        err.to_py_err(py)
+    }
+}
+
+/// Extension trait for `Result`s with errors that can be converted into python exceptions.
+pub trait PyResultExt<'p, T> {
+    /// Converts the error into a `PyErr`, so that `try!` can be used
+    /// in functions returning `PyResult`.
+    fn py_err(self, py: Python<'p>) -> PyResult<'p, T>;
+}
+
+impl <'p, T, E> PyResultExt<'p, T> for Result<T, E> where E: ToPyErr {
+    #[inline]
+    fn py_err(self, py: Python<'p>) -> PyResult<'p, T> {
+        self.map_err(|e| e.to_py_err(py))
+    }
+}
+
+/// Maps the error kind to the corresponding `OSError` subclass.
+/// OS errors are passed to the constructor as `(errno, strerror)`.
+impl ToPyErr for io::Error {
+    #[cfg(feature="python27-sys")]
+    fn exception_type<'p>(&self, py: Python<'p>) -> PyType<'p> {
+        py.get_type::<exc::IOError>()
+    }
+
+    #[cfg(feature="python3-sys")]
+    fn exception_type<'p>(&self, py: Python<'p>) -> PyType<'p> {
+        match self.kind() {
+            io::ErrorKind::NotFound => py.get_type::<exc::FileNotFoundError>(),
+            io::ErrorKind::PermissionDenied => py.get_type::<exc::PermissionError>(),
+            io::ErrorKind::AlreadyExists => py.get_type::<exc::FileExistsError>(),
+            io::ErrorKind::ConnectionRefused => py.get_type::<exc::ConnectionRefusedError>(),
+            io::ErrorKind::ConnectionReset => py.get_type::<exc::ConnectionResetError>(),
+            io::ErrorKind::ConnectionAborted => py.get_type::<exc::ConnectionAbortedError>(),
+            io::ErrorKind::BrokenPipe => py.get_type::<exc::BrokenPipeError>(),
+            io::ErrorKind::WouldBlock => py.get_type::<exc::BlockingIOError>(),
+            io::ErrorKind::TimedOut => py.get_type::<exc::TimeoutError>(),
+            io::ErrorKind::Interrupted => py.get_type::<exc::InterruptedError>(),
+            _ => py.get_type::<exc::OSError>()
+        }
+    }
+
+    fn exception_value<'p>(&self, py: Python<'p>) -> PyObject<'p> {
+        let msg = self.to_string();
+        match self.raw_os_error() {
+            Some(errno) => {
+                // Python adds the errno to the message on its own.
+                let suffix = format!(" (os error {})", errno);
+                let strerror = if msg.ends_with(&suffix) { &msg[..msg.len() - suffix.len()] } else { &msg[..] };
+                (errno, strerror).to_py_object(py).into_object()
+            }
+            None => msg.to_py_object(py).into_object()
+        }
+    }
+}
+
+impl ToPyErr for ParseIntError {
+    fn exception_type<'p>(&self, py: Python<'p>) -> PyType<'p> {
+        py.get_type::<exc::ValueError>()
+    }
+}
+
+impl ToPyErr for NulError {
+    fn exception_type<'p>(&self, py: Python<'p>) -> PyType<'p> {
+        py.get_type::<exc::ValueError>()
+    }
+}
+
+impl ToPyErr for ParseFloatError {
+    fn exception_type<'p>(&self, py: Python<'p>) -> PyType<'p> {
+        py.get_type::<exc::ValueError>()
     }
 }
 
@@ -243,6 +739,16 @@ pub unsafe fn result_from_owned_ptr(py : Python, p : *mut ffi::PyObject) -> PyRe
     }
 }
 
+/// Concatenates the list of lines returned by the `traceback` module functions.
+pub fn join_lines<'p>(lines: &PyObject<'p>) -> PyResult<'p, String> {
+    use objectprotocol::ObjectProtocol;
+    let mut s = String::new();
+    for line in try!(lines.iter()) {
+        s.push_str(&try!(try!(line).extract::<String>()));
+    }
+    Ok(s)
+}
+
 fn panic_after_error(py: Python) -> ! {
     unsafe { ffi::PyErr_Print(); }
     panic!("Python API called failed");
@@ -301,7 +807,7 @@ pub fn error_on_minusone(py : Python, result : libc::c_int) -> PyResult<()> {
 
 #[cfg(test)]
 mod tests {
-    use {Python, PyErr};
+    use {Python, PythonObject, PyErr, PyResult, PyResultExt};
     use objects::{PyObject, exc};
     
     #[test]
@@ -312,6 +818,137 @@ mod tests {
         assert!(PyErr::occurred(py));
         drop(PyErr::fetch(py));
     }
+
+    #[test]
+    fn typed_constructors() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut err = PyErr::new::<exc::ValueError, _>(py, ("bad input", 3));
+        assert!(err.matches(py.get_type::<exc::ValueError>().as_object()));
+        assert_eq!("('bad input', 3)", format!("{:?}", err.instance()));
+        let mut err = exc::KeyError::new(py, "key");
+        assert!(err.matches(py.get_type::<exc::KeyError>().as_object()));
+        assert_eq!("'key'", format!("{:?}", err.instance()));
+        let mut err = py_err!(py, exc::TypeError, "bad input: {}", 3);
+        assert!(err.matches(py.get_type::<exc::TypeError>().as_object()));
+        assert_eq!("bad input: 3", format!("{:?}", err.instance()));
+    }
+
+    #[test]
+    fn display() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = exc::ValueError::new(py, "bad input");
+        assert_eq!("ValueError: bad input", err.to_string());
+        let err = PyErr::new_lazy_init(py.get_type::<exc::KeyError>(), None);
+        assert_eq!("KeyError", err.to_string());
+    }
+
+    #[test]
+    fn from_rust_errors() {
+        use std::io;
+        fn parse<'p>(py: Python<'p>, s: &str) -> PyResult<'p, i32> {
+            let n = try!(s.parse::<i32>().py_err(py));
+            Ok(n)
+        }
+        fn open<'p>(py: Python<'p>) -> PyResult<'p, ()> {
+            try!(Err::<(), _>(io::Error::from_raw_os_error(2)).py_err(py));
+            Ok(())
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = parse(py, "x").unwrap_err();
+        assert!(err.matches(py.get_type::<exc::ValueError>().as_object()));
+        let err = open(py).unwrap_err();
+        assert!(err.matches(py.get_type::<exc::EnvironmentError>().as_object()));
+        assert!(err.to_string().contains("No such file or directory"));
+        let err = PyErr::from((py, "1.5x".parse::<f64>().unwrap_err()));
+        assert!(err.matches(py.get_type::<exc::ValueError>().as_object()));
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn chaining() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut err = exc::RuntimeError::new(py, "outer").with_cause(exc::KeyError::new(py, "inner"));
+        assert!(err.cause().unwrap().matches(py.get_type::<exc::KeyError>().as_object()));
+        assert!(err.suppress_context());
+
+        let mut err = exc::ValueError::new(py, "second").with_exception_context(exc::KeyError::new(py, "first"));
+        assert!(err.context().unwrap().matches(py.get_type::<exc::KeyError>().as_object()));
+        assert!(!err.suppress_context());
+        assert!(err.format().unwrap().contains("During handling of the above exception"));
+
+        // restore() only chains the exception being handled, not a pending error
+        exc::KeyError::new(py, "first").restore();
+        exc::ValueError::new(py, "second").restore();
+        let mut err = PyErr::fetch(py);
+        assert!(err.matches(py.get_type::<exc::ValueError>().as_object()));
+        assert!(err.context().is_none());
+    }
+
+    #[test]
+    #[cfg(feature="python27-sys")]
+    fn chaining() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut err = exc::RuntimeError::new(py, "outer").with_cause(exc::KeyError::new(py, "inner"));
+        assert_eq!("RuntimeError: outer (caused by KeyError: 'inner')", err.to_string());
+        assert!(err.cause().unwrap().matches(py.get_type::<exc::KeyError>().as_object()));
+        assert!(err.suppress_context());
+
+        let mut err = exc::ValueError::new(py, "second").with_exception_context(exc::KeyError::new(py, "first"));
+        assert_eq!("ValueError: second (raised while handling KeyError: 'first')", err.to_string());
+        assert!(err.context().unwrap().matches(py.get_type::<exc::KeyError>().as_object()));
+    }
+
+    #[test]
+    fn automatic_context() {
+        use objects::{PyCallable, PyDict};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let fail = PyCallable::from_closure(py, "fail", |py, _, _| Err(exc::ValueError::new(py, "second"))).unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("fail", fail).unwrap();
+        let mut err = py.run("try:\n    raise KeyError('first')\nexcept KeyError:\n    fail()\n",
+            Some(&globals), None).unwrap_err();
+        assert!(err.matches(py.get_type::<exc::ValueError>().as_object()));
+        assert!(err.context().unwrap().matches(py.get_type::<exc::KeyError>().as_object()));
+        let formatted = err.format().unwrap();
+        if cfg!(feature="python27-sys") {
+            assert!(formatted.contains("ValueError: second (raised while handling KeyError: 'first')"));
+        } else {
+            assert!(formatted.contains("KeyError: 'first'"));
+            assert!(formatted.contains("During handling of the above exception"));
+            assert!(formatted.contains("ValueError: second"));
+        }
+    }
+
+    #[test]
+    fn nul_in_name() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = PyErr::new_type(py, "mymodule.Bad\0Name", None, None).unwrap_err();
+        assert!(err.matches(py.get_type::<exc::ValueError>().as_object()));
+        assert!(py.import("no\0such_module").is_err());
+    }
+
+    py_exception!(mymodule, CustomError, exc::ValueError);
+
+    #[test]
+    fn custom_exception() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let t = py.get_type::<CustomError>();
+        assert!(t == py.get_type::<CustomError>());
+        assert!(t == CustomError::try_type_object(py).unwrap());
+        assert!(t.is_subtype_of(&py.get_type::<exc::ValueError>()));
+        let err = PyErr::new_lazy_init(t, None);
+        assert!(err.matches(py.get_type::<CustomError>().as_object()));
+        assert!(err.matches(py.get_type::<exc::ValueError>().as_object()));
+    }
 }
 
 
@@ -0,0 +1,37 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+
+//! Helper for implementing python protocols with Rust closures.
+
+use python::{Python, PythonObject};
+use objects::{PyObject, PyType, PyDict, PyTuple, PyCallable};
+use objectprotocol::ObjectProtocol;
+use err::PyResult;
+
+/// Creates a class with the given functions as attributes.
+/// Builtin functions are not bound as methods, so the class object itself
+/// can be used as an object implementing a python protocol.
+pub fn new_function_class<'p>(py: Python<'p>, name: &str, functions: Vec<(&str, PyCallable<'p>)>) -> PyResult<'p, PyObject<'p>> {
+    let dict = try!(PyDict::try_new(py));
+    for (attr, function) in functions {
+        try!(dict.set_item(attr, function.into_object()));
+    }
+    let bases = try!(PyTuple::try_empty(py)).into_object();
+    py.get_type::<PyType>().as_object().call(&(name, bases, dict.into_object()), None)
+}
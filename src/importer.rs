@@ -0,0 +1,350 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Import hooks that load python modules from Rust.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use python::{Python, PythonObject};
+use objects::{PyObject, PyDict, PyList, PyModule, PyCallable, PyCode, CompileMode, exc};
+use objectprotocol::ObjectProtocol;
+use owned::Py;
+use functionclass::new_function_class;
+use err::PyResult;
+
+/// Describes a module that can be loaded by an `Importer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleInfo {
+    /// The value of the module's `__file__` attribute (the origin of the module spec on python 3).
+    pub filename: String,
+    /// Whether the module is a package, i.e. may contain submodules.
+    pub is_package: bool
+}
+
+/// A module finder and loader implemented in Rust.
+///
+/// Use `install_importer()` to make python use the importer for `import` statements.
+pub trait Importer : Send + Sync + 'static {
+    /// Looks up the module with the fully qualified name `fullname`.
+    /// Returns `None` if the module is not provided by this importer.
+    fn find_module(&self, fullname: &str) -> Option<ModuleInfo>;
+
+    /// Gets the code object for a module that was found by `find_module()`.
+    /// The code is executed in the namespace of the new module.
+    fn get_code<'p>(&self, py: Python<'p>, fullname: &str) -> PyResult<'p, PyCode<'p>>;
+}
+
+enum ModuleCode {
+    Source(String),
+    Bytecode(Vec<u8>)
+}
+
+/// An `Importer` that serves modules from an in-memory map.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, MemoryImporter, install_importer};
+///
+/// let mut importer = MemoryImporter::new();
+/// importer.add_source("greeting", "def hello(name):\n    return 'Hello, ' + name\n", false);
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// install_importer(py, importer).unwrap();
+/// let greeting = py.import("greeting").unwrap();
+/// ```
+pub struct MemoryImporter {
+    modules: HashMap<String, (ModuleInfo, ModuleCode)>
+}
+
+impl MemoryImporter {
+    /// Creates an importer without any modules.
+    pub fn new() -> MemoryImporter {
+        MemoryImporter { modules: HashMap::new() }
+    }
+
+    /// Adds a module given as python source code.
+    ///
+    /// For packages, `source` is the code of the package's `__init__` module.
+    pub fn add_source(&mut self, fullname: &str, source: &str, is_package: bool) {
+        self.add(fullname, ModuleCode::Source(source.to_owned()), is_package);
+    }
+
+    /// Adds a precompiled module, serialized using `PyCode::to_bytes()`.
+    ///
+    /// The bytecode must have been produced by the same python version.
+    pub fn add_bytecode(&mut self, fullname: &str, bytecode: &[u8], is_package: bool) {
+        self.add(fullname, ModuleCode::Bytecode(bytecode.to_vec()), is_package);
+    }
+
+    fn add(&mut self, fullname: &str, code: ModuleCode, is_package: bool) {
+        let path = fullname.replace(".", "/");
+        let filename = if is_package {
+            format!("<memory>/{}/__init__.py", path)
+        } else {
+            format!("<memory>/{}.py", path)
+        };
+        let info = ModuleInfo { filename: filename, is_package: is_package };
+        self.modules.insert(fullname.to_owned(), (info, code));
+    }
+}
+
+impl Importer for MemoryImporter {
+    fn find_module(&self, fullname: &str) -> Option<ModuleInfo> {
+        self.modules.get(fullname).map(|&(ref info, _)| info.clone())
+    }
+
+    fn get_code<'p>(&self, py: Python<'p>, fullname: &str) -> PyResult<'p, PyCode<'p>> {
+        match self.modules.get(fullname) {
+            Some(&(ref info, ModuleCode::Source(ref source))) =>
+                Ok(try!(py.compile(source, &info.filename, CompileMode::Exec))),
+            Some(&(_, ModuleCode::Bytecode(ref bytecode))) =>
+                PyCode::from_bytes(py, bytecode),
+            None => Err(no_module(py, fullname))
+        }
+    }
+}
+
+/// Installs the importer at the front of `sys.meta_path`.
+///
+/// On python 3, the hook implements the `find_spec()`/`exec_module()` protocol,
+/// so imported modules get a `__spec__`.
+/// On python 2, the hook is a PEP 302 importer with `find_module()`/`load_module()`.
+///
+/// Returns the hook object, which can be removed from `sys.meta_path` to uninstall the importer.
+pub fn install_importer<'p, I>(py: Python<'p>, importer: I) -> PyResult<'p, PyObject<'p>> where I: Importer {
+    let importer = Arc::new(importer);
+    let loader = try!(new_loader(py, importer.clone()));
+    let finder = try!(new_finder(py, importer, loader));
+    let meta_path = try!(try!(try!(py.import("sys")).get("meta_path")).cast_into::<PyList>());
+    meta_path.insert_item(0, finder.clone());
+    Ok(finder)
+}
+
+fn no_module<'p>(py: Python<'p>, fullname: &str) -> ::err::PyErr<'p> {
+    py_err!(py, exc::ImportError, "No module named {}", fullname)
+}
+
+/// Gets the directory that is used as `__path__` of a package.
+fn package_path(info: &ModuleInfo) -> String {
+    match Path::new(&info.filename).parent() {
+        Some(parent) => parent.to_string_lossy().into_owned(),
+        None => info.filename.clone()
+    }
+}
+
+#[cfg(feature="python3-sys")]
+fn new_finder<'p, I>(py: Python<'p>, importer: Arc<I>, loader: PyObject<'p>) -> PyResult<'p, PyObject<'p>> where I: Importer {
+    let loader = Py::<PyObject<'static>>::new(loader);
+    let find_spec = try!(PyCallable::from_closure(py, "find_spec", move |py, args, _kwargs| {
+        let fullname = try!(args.extract_arg::<String>(0, "fullname"));
+        match importer.find_module(&fullname) {
+            Some(info) => module_spec(py, &fullname, &info, loader.get(py)),
+            None => Ok(py.None())
+        }
+    }));
+    new_function_class(py, "RustFinder", vec![("find_spec", find_spec)])
+}
+
+#[cfg(feature="python3-sys")]
+fn module_spec<'p>(py: Python<'p>, fullname: &str, info: &ModuleInfo, loader: PyObject<'p>) -> PyResult<'p, PyObject<'p>> {
+    let machinery = try!(py.import("importlib.machinery"));
+    let kwargs = PyDict::new(py);
+    try!(kwargs.set_item("origin", &info.filename[..]));
+    try!(kwargs.set_item("is_package", info.is_package));
+    let spec = try!(try!(machinery.get("ModuleSpec")).call(&(fullname, loader), Some(&kwargs)));
+    // has_location makes the import machinery set __file__ to the origin.
+    try!(spec.setattr("has_location", &true));
+    if info.is_package {
+        let path = PyList::new(py, &[]);
+        try!(path.append(&package_path(info)[..]));
+        try!(spec.setattr("submodule_search_locations", &path.into_object()));
+    }
+    Ok(spec)
+}
+
+#[cfg(feature="python3-sys")]
+fn new_loader<'p, I>(py: Python<'p>, importer: Arc<I>) -> PyResult<'p, PyObject<'p>> where I: Importer {
+    let create_module = try!(PyCallable::from_closure(py, "create_module", |py, _args, _kwargs| {
+        // Use the default module creation.
+        Ok(py.None())
+    }));
+    let exec_module = try!(PyCallable::from_closure(py, "exec_module", move |py, args, _kwargs| {
+        let module = try!(try!(args.extract_arg::<PyObject>(0, "module")).cast_into::<PyModule>());
+        let fullname = try!(module.name()).to_owned();
+        let code = try!(importer.get_code(py, &fullname));
+        try!(code.run(Some(&module.dict()), None));
+        Ok(py.None())
+    }));
+    new_function_class(py, "RustLoader", vec![("create_module", create_module), ("exec_module", exec_module)])
+}
+
+#[cfg(feature="python27-sys")]
+fn new_finder<'p, I>(py: Python<'p>, importer: Arc<I>, loader: PyObject<'p>) -> PyResult<'p, PyObject<'p>> where I: Importer {
+    let loader = Py::<PyObject<'static>>::new(loader);
+    let find_module = try!(PyCallable::from_closure(py, "find_module", move |py, args, _kwargs| {
+        let fullname = try!(args.extract_arg::<String>(0, "fullname"));
+        match importer.find_module(&fullname) {
+            Some(_) => Ok(loader.get(py)),
+            None => Ok(py.None())
+        }
+    }));
+    new_function_class(py, "RustFinder", vec![("find_module", find_module)])
+}
+
+#[cfg(feature="python27-sys")]
+fn new_loader<'p, I>(py: Python<'p>, importer: Arc<I>) -> PyResult<'p, PyObject<'p>> where I: Importer {
+    use std::sync::Mutex;
+    use objects::NoArgs;
+    // The loader is stored in the module's __loader__, but load_module() is called
+    // without it, and the loader is only known after the class was created.
+    // A weak reference avoids a cycle through load_module() that the GC cannot collect.
+    let loader_cell: Arc<Mutex<Option<Py<PyObject<'static>>>>> = Arc::new(Mutex::new(None));
+    let loader_ref = loader_cell.clone();
+    let load_module_fn = try!(PyCallable::from_closure(py, "load_module", move |py, args, _kwargs| {
+        let fullname = try!(args.extract_arg::<String>(0, "fullname"));
+        let loader = match *loader_ref.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(ref weak_loader) => try!(weak_loader.get(py).call(&NoArgs, None)),
+            None => py.None()
+        };
+        load_module(py, &*importer, &fullname, loader)
+    }));
+    let loader = try!(new_function_class(py, "RustLoader", vec![("load_module", load_module_fn)]));
+    let weakref = try!(py.import("weakref"));
+    let weak_loader = try!(weakref.as_object().call_method("ref", &(loader.clone(),), None));
+    *loader_cell.lock().unwrap_or_else(|e| e.into_inner()) = Some(Py::new(weak_loader));
+    Ok(loader)
+}
+
+/// Implements the PEP 302 `load_module()` protocol.
+#[cfg(feature="python27-sys")]
+fn load_module<'p, I>(py: Python<'p>, importer: &I, fullname: &str, loader: PyObject<'p>) -> PyResult<'p, PyObject<'p>> where I: Importer {
+    let info = match importer.find_module(fullname) {
+        Some(info) => info,
+        None => return Err(no_module(py, fullname))
+    };
+    let code = try!(importer.get_code(py, fullname));
+    let modules = try!(try!(try!(py.import("sys")).get("modules")).cast_into::<PyDict>());
+    // If the module is being reloaded, the existing module object must be reused.
+    let (module, is_new) = match modules.get_item(fullname) {
+        Some(module) => (try!(module.cast_into::<PyModule>()), false),
+        None => (try!(PyModule::new(py, fullname)), true)
+    };
+    try!(modules.set_item(fullname, module.as_object()));
+    let result = init_module(py, &module, &info, &code, loader, fullname);
+    if let Err(e) = result {
+        if is_new {
+            let _ = modules.del_item(fullname);
+        }
+        return Err(e);
+    }
+    // The module code may have replaced itself in sys.modules.
+    Ok(modules.get_item(fullname).unwrap_or(module.into_object()))
+}
+
+#[cfg(feature="python27-sys")]
+fn init_module<'p>(py: Python<'p>, module: &PyModule<'p>, info: &ModuleInfo, code: &PyCode<'p>,
+                   loader: PyObject<'p>, fullname: &str) -> PyResult<'p, ()> {
+    try!(module.add("__file__", &info.filename[..]));
+    try!(module.add("__loader__", loader));
+    if info.is_package {
+        try!(module.add("__package__", fullname));
+        let path = PyList::new(py, &[]);
+        try!(path.append(&package_path(info)[..]));
+        try!(module.add("__path__", path.into_object()));
+    } else {
+        let package = match fullname.rfind('.') {
+            Some(pos) => &fullname[..pos],
+            None => ""
+        };
+        try!(module.add("__package__", package));
+    }
+    try!(code.run(Some(&module.dict()), None));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use python::Python;
+    use objects::CompileMode;
+    use objectprotocol::ObjectProtocol;
+    use super::{MemoryImporter, install_importer};
+
+    #[test]
+    fn test_memory_importer() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytecode = py.compile("VALUE = 'compiled'", "<memory>/rustpkg/compiled.py", CompileMode::Exec)
+            .unwrap().to_bytes().unwrap();
+        let mut importer = MemoryImporter::new();
+        importer.add_source("rustpkg", "NAME = __name__", true);
+        importer.add_source("rustpkg.sub", "from rustpkg import NAME\nVALUE = NAME + '.sub'", false);
+        importer.add_bytecode("rustpkg.compiled", &bytecode, false);
+        importer.add_source("rustpkg.broken", "raise ValueError('broken')", false);
+        let hook = install_importer(py, importer).unwrap();
+
+        let pkg = py.import("rustpkg").unwrap();
+        assert_eq!(pkg.get("NAME").unwrap().extract::<String>().unwrap(), "rustpkg");
+        assert_eq!(pkg.get("__file__").unwrap().extract::<String>().unwrap(), "<memory>/rustpkg/__init__.py");
+        assert!(pkg.get("__path__").is_ok());
+
+        let sub = py.import("rustpkg.sub").unwrap();
+        assert_eq!(sub.get("VALUE").unwrap().extract::<String>().unwrap(), "rustpkg.sub");
+        assert_eq!(sub.get("__package__").unwrap().extract::<String>().unwrap(), "rustpkg");
+
+        let compiled = py.import("rustpkg.compiled").unwrap();
+        assert_eq!(compiled.get("VALUE").unwrap().extract::<String>().unwrap(), "compiled");
+
+        assert!(py.import("rustpkg.broken").is_err());
+        assert!(py.import("rustpkg.missing").is_err());
+
+        let meta_path = py.import("sys").unwrap().get("meta_path").unwrap();
+        meta_path.call_method("remove", &(hook,), None).unwrap();
+    }
+
+    #[cfg(feature="python27-sys")]
+    #[test]
+    fn test_loader_collectable() {
+        use std::sync::Arc;
+        use objects::NoArgs;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let loader = super::new_loader(py, Arc::new(MemoryImporter::new())).unwrap();
+        let weakref = py.import("weakref").unwrap();
+        let weak_loader = weakref.as_object().call_method("ref", &(loader,), None).unwrap();
+        py.import("gc").unwrap().as_object().call_method("collect", &NoArgs, None).unwrap();
+        assert!(weak_loader.call(&NoArgs, None).unwrap() == py.None());
+    }
+
+    #[cfg(feature="python3-sys")]
+    #[test]
+    fn test_spec() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut importer = MemoryImporter::new();
+        importer.add_source("rustspec", "", false);
+        let hook = install_importer(py, importer).unwrap();
+        let module = py.import("rustspec").unwrap();
+        let spec = module.get("__spec__").unwrap();
+        assert_eq!(spec.getattr("name").unwrap().extract::<String>().unwrap(), "rustspec");
+        assert_eq!(spec.getattr("origin").unwrap().extract::<String>().unwrap(), "<memory>/rustspec.py");
+        assert_eq!(module.get("__file__").unwrap().extract::<String>().unwrap(), "<memory>/rustspec.py");
+        let meta_path = py.import("sys").unwrap().get("meta_path").unwrap();
+        meta_path.call_method("remove", &(hook,), None).unwrap();
+    }
+}
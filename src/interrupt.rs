@@ -0,0 +1,194 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Interrupting python code from other threads.
+
+use std::{ptr, thread};
+use libc::{c_int, c_long, c_void};
+use ffi;
+use python::{Python, PythonObject, ToPythonPointer};
+use objects::{PyType, exc};
+use err::{self, PyResult};
+
+/// Identifies a python thread.
+///
+/// Obtained from `GILGuard::thread_handle()` on the thread running the python code;
+/// the handle can then be sent to a supervising thread.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PyThreadHandle {
+    id: c_long
+}
+
+impl PyThreadHandle {
+    /// Gets the handle of the current thread.
+    /// Requires the GIL, as python may only identify threads that have a thread state.
+    pub fn current(_py: Python) -> PyThreadHandle {
+        PyThreadHandle { id: unsafe { ffi::PyThread_get_thread_ident() } }
+    }
+
+    /// Asynchronously raises an exception of type `exc` in the thread,
+    /// using `PyThreadState_SetAsyncExc()`.
+    ///
+    /// The exception is raised the next time the thread executes python bytecode;
+    /// it is not raised while the thread is blocked in native code.
+    /// Returns `Ok(false)` if the thread does not exist (anymore).
+    ///
+    /// If python reports that more than one thread state was affected,
+    /// the exception is revoked again and `SystemError` is returned.
+    pub fn raise_async<'p>(&self, exc_type: &PyType<'p>) -> PyResult<'p, bool> {
+        let py = exc_type.python();
+        unsafe {
+            match ffi::PyThreadState_SetAsyncExc(self.id, exc_type.as_ptr()) {
+                0 => Ok(false),
+                1 => Ok(true),
+                n => {
+                    ffi::PyThreadState_SetAsyncExc(self.id, ptr::null_mut());
+                    Err(py_err!(py, exc::SystemError,
+                        "PyThreadState_SetAsyncExc affected {} threads", n))
+                }
+            }
+        }
+    }
+
+    /// Clears an exception scheduled by `raise_async()` that wasn't raised yet.
+    pub fn cancel_async(&self, _py: Python) {
+        unsafe { ffi::PyThreadState_SetAsyncExc(self.id, ptr::null_mut()); }
+    }
+}
+
+/// Schedules `f` to be called from the main python thread using `Py_AddPendingCall()`.
+///
+/// This function may be called from any thread, even without holding the GIL.
+/// If `f` returns an error, the exception is raised in the main thread,
+/// which allows interrupting long-running python code.
+/// Returns `false` if the queue of pending calls is full.
+///
+/// Note that the 'main thread' is the thread that initialized python;
+/// the call is delayed until that thread executes python code.
+/// If `f` panics, the panic is caught and `SystemError` is raised instead.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use cpython::{Python, PyErr, PythonObject, add_pending_call};
+/// use cpython::exc::KeyboardInterrupt;
+///
+/// // Initializes python; this thread becomes the main python thread.
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let called = Arc::new(AtomicBool::new(false));
+/// let flag = called.clone();
+/// assert!(add_pending_call(move |py| {
+///     flag.store(true, Ordering::SeqCst);
+///     Err(PyErr::new::<KeyboardInterrupt, _>(py, "stop"))
+/// }));
+/// let err = py.run("while True:\n    pass", None, None).unwrap_err();
+/// assert!(called.load(Ordering::SeqCst));
+/// assert!(err.matches(py.get_type::<KeyboardInterrupt>().as_object()));
+/// ```
+pub fn add_pending_call<F>(f: F) -> bool where F: for<'p> FnOnce(Python<'p>) -> PyResult<'p, ()> + Send + 'static {
+    let f = Box::into_raw(Box::new(f));
+    unsafe {
+        if ffi::Py_AddPendingCall(Some(run_pending_call::<F>), f as *mut c_void) == 0 {
+            true
+        } else {
+            drop(Box::from_raw(f));
+            false
+        }
+    }
+}
+
+extern "C" fn run_pending_call<F>(arg: *mut c_void) -> c_int
+    where F: for<'p> FnOnce(Python<'p>) -> PyResult<'p, ()> + Send + 'static
+{
+    let f = unsafe { Box::from_raw(arg as *mut F) };
+    // Unwinding into the python interpreter is undefined behavior.
+    let result = thread::catch_panic(move || {
+        let py = unsafe { Python::assume_gil_acquired() };
+        match (*f)(py) {
+            Ok(()) => 0,
+            Err(e) => {
+                e.restore();
+                -1
+            }
+        }
+    });
+    match result {
+        Ok(ret) => ret,
+        Err(_) => {
+            let py = unsafe { Python::assume_gil_acquired() };
+            py_err!(py, exc::SystemError, "panic in pending call").restore();
+            -1
+        }
+    }
+}
+
+/// Simulates the arrival of `SIGINT` using `PyErr_SetInterrupt()`:
+/// the main thread raises `KeyboardInterrupt` the next time it checks for signals.
+///
+/// This function may be called from any thread, even without holding the GIL.
+/// It has no effect unless python signal handling was enabled using
+/// `InterpreterBuilder::install_signal_handlers()`.
+pub fn set_interrupt() {
+    unsafe { ffi::PyErr_SetInterrupt() }
+}
+
+impl <'p> Python<'p> {
+    /// Runs the python handlers for pending signals using `PyErr_CheckSignals()`.
+    ///
+    /// Long-running Rust code called from python should call this periodically,
+    /// so that `KeyboardInterrupt` (or an exception raised by a signal handler) is propagated.
+    pub fn check_signals(self) -> PyResult<'p, ()> {
+        unsafe { err::error_on_minusone(self, ffi::PyErr_CheckSignals()) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+    use std::sync::mpsc;
+    use python::Python;
+    use objects::exc;
+
+    #[test]
+    fn test_raise_async() {
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            sender.send(gil.thread_handle()).unwrap();
+            let mut err = py.run("while True:\n    pass", None, None).unwrap_err();
+            let is_runtime_error = err.instance().cast_into::<exc::RuntimeError>().is_ok();
+            is_runtime_error
+        });
+        let handle = receiver.recv().unwrap();
+        {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            assert!(handle.raise_async(&py.get_type::<exc::RuntimeError>()).unwrap());
+        }
+        assert!(worker.join().unwrap());
+    }
+
+    #[test]
+    fn test_check_signals() {
+        let gil = Python::acquire_gil();
+        gil.python().check_signals().unwrap();
+    }
+}
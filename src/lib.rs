@@ -22,6 +22,8 @@
 #![feature(optin_builtin_traits)] // for opting out of Sync/Send
 #![feature(slice_patterns)] // for tuple_conversion macros
 #![feature(utf8_error)] // for translating Utf8Error to python exception
+#![feature(box_raw)] // for passing boxed Rust state through python capsules
+#![feature(catch_panic)] // for not unwinding into python from pending calls and stream callbacks
 #![feature(plugin)]
 #![plugin(interpolate_idents)]
 #![allow(unused_imports, unused_variables)]
@@ -82,12 +84,18 @@ extern crate python27_sys as ffi;
 extern crate python3_sys as ffi;
 
 pub use ffi::Py_ssize_t;
-pub use err::{PyErr, PyResult};
+pub use err::{PyErr, PyResult, PyResultExt, ToPyErr};
 pub use objects::*;
-pub use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectWithTypeObject, ToPythonPointer};
-pub use pythonrun::{GILGuard, prepare_freethreaded_python};
+pub use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectDowncastError, PythonObjectWithTypeObject, PythonObjectRebind, ToPythonPointer};
+pub use owned::Py;
+pub use pythonrun::{GILGuard, prepare_freethreaded_python, InterpreterBuilder, InterpreterError, finalize};
 pub use conversion::{FromPyObject, ToPyObject};
 pub use objectprotocol::{ObjectProtocol};
+pub use warnings::{CatchWarnings, WarningAction, WarningMessage};
+pub use importer::{Importer, ModuleInfo, MemoryImporter, install_importer};
+pub use subinterpreter::{SubInterpreter, Isolated};
+pub use sys::{PySys, StdStream, RedirectGuard};
+pub use interrupt::{PyThreadHandle, add_pending_call, set_interrupt};
 
 /// Constructs a `&'static CStr` literal.
 macro_rules! cstr(
@@ -99,19 +107,232 @@ macro_rules! cstr(
     );
 );
 
+/// Constructs keyword arguments for `PyCallable::call_kw()`.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PythonObject, PyCallable, NoArgs};
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     let dict = py.get_type::<cpython::PyDict>().into_object().cast_into::<PyCallable>().unwrap();
+///     let d = dict.call_kw(NoArgs, kwargs!(x = 1, y = "a")).unwrap();
+///     assert_eq!(2, d.extract::<cpython::PyDict>().unwrap().len());
+/// }
+/// ```
+#[macro_export]
+macro_rules! kwargs {
+    () => ( () );
+    ($name: ident = $value: expr $(, $rest_name: ident = $rest_value: expr)*) => (
+        $crate::KwArg(stringify!($name), $value, kwargs!($($rest_name = $rest_value),*))
+    );
+    ($name: ident = $value: expr $(, $rest_name: ident = $rest_value: expr)* ,) => (
+        kwargs!($name = $value $(, $rest_name = $rest_value)*)
+    );
+}
+
+/// Creates a `PyErr` of the given exception type with a `format!`-style message.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyErr, PyResult, PythonObject};
+/// use cpython::exc::ValueError;
+///
+/// fn check<'p>(py: Python<'p>, n: i32) -> PyResult<'p, ()> {
+///     if n < 0 {
+///         return Err(py_err!(py, ValueError, "bad input: {}", n));
+///     }
+///     Ok(())
+/// }
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     let err = check(py, -3).unwrap_err();
+///     assert!(err.matches(py.get_type::<ValueError>().as_object()));
+/// }
+/// ```
+#[macro_export]
+macro_rules! py_err {
+    ($py: expr, $exc: ty, $($arg: tt)+) => (
+        $crate::PyErr::new::<$exc, _>($py, format!($($arg)+))
+    );
+}
+
+/// Defines a new python exception class.
+///
+/// The macro takes the module name, the exception name, and optionally
+/// the base exception type (defaulting to `exc::Exception`) and a docstring.
+/// The python type object is created on first use and cached for the
+/// lifetime of the program; `Name::try_type_object(py)` reports failures
+/// to create it.
+/// Use `py.get_type::<Name>()` to raise the exception or to add it to a module:
+/// `m.add("ParseError", py.get_type::<ParseError>().into_object())`.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyErr, PythonObject};
+/// use cpython::exc::ValueError;
+///
+/// py_exception!(mylib, ParseError, ValueError);
+///
+/// fn main() {
+///     let gil = Python::acquire_gil();
+///     let py = gil.python();
+///     let err = ParseError::new(py, "unexpected end of input");
+///     assert!(err.matches(py.get_type::<ValueError>().as_object()));
+/// }
+/// ```
+#[macro_export]
+macro_rules! py_exception {
+    ($module: ident, $name: ident) => (
+        py_exception!($module, $name, $crate::exc::Exception);
+    );
+    ($module: ident, $name: ident, $base: ty) => (
+        py_exception!($module, $name, $base, None);
+    );
+    ($module: ident, $name: ident, $base: ty, $doc: expr) => (
+        #[repr(C)]
+        #[derive(Clone)]
+        pub struct $name<'p>($crate::PyObject<'p>);
+
+        impl <'p> $crate::ToPythonPointer for $name<'p> {
+            #[inline]
+            fn as_ptr(&self) -> *mut $crate::_detail::ffi::PyObject {
+                $crate::ToPythonPointer::as_ptr(&self.0)
+            }
+
+            #[inline]
+            fn steal_ptr(self) -> *mut $crate::_detail::ffi::PyObject {
+                $crate::ToPythonPointer::steal_ptr(self.0)
+            }
+        }
+
+        impl <'p> $crate::PythonObject<'p> for $name<'p> {
+            #[inline]
+            fn as_object(&self) -> &$crate::PyObject<'p> {
+                &self.0
+            }
+
+            #[inline]
+            fn into_object(self) -> $crate::PyObject<'p> {
+                self.0
+            }
+
+            #[inline]
+            unsafe fn unchecked_downcast_from(obj: $crate::PyObject<'p>) -> Self {
+                $name(obj)
+            }
+
+            #[inline]
+            unsafe fn unchecked_downcast_borrow_from<'a>(obj: &'a $crate::PyObject<'p>) -> &'a Self {
+                ::std::mem::transmute(obj)
+            }
+        }
+
+        unsafe impl <'p, 's> $crate::PythonObjectRebind<'p> for $name<'s> {
+            type Output = $name<'p>;
+        }
+
+        impl <'p> $crate::PythonObjectWithCheckedDowncast<'p> for $name<'p> {
+            #[inline]
+            fn downcast_from(obj: $crate::PyObject<'p>) -> Result<$name<'p>, $crate::PythonObjectDowncastError<'p>> {
+                let py = $crate::PythonObject::python(&obj);
+                let t = <$name as $crate::PythonObjectWithTypeObject>::type_object(py);
+                if t.is_instance(&obj) {
+                    Ok($name(obj))
+                } else {
+                    Err($crate::PythonObjectDowncastError::new(t, &obj))
+                }
+            }
+
+            #[inline]
+            fn downcast_borrow_from<'a>(obj: &'a $crate::PyObject<'p>) -> Result<&'a $name<'p>, $crate::PythonObjectDowncastError<'p>> {
+                let py = $crate::PythonObject::python(obj);
+                let t = <$name as $crate::PythonObjectWithTypeObject>::type_object(py);
+                if t.is_instance(obj) {
+                    Ok(unsafe { ::std::mem::transmute(obj) })
+                } else {
+                    Err($crate::PythonObjectDowncastError::new(t, obj))
+                }
+            }
+        }
+
+        impl <'p> $crate::PythonObjectWithTypeObject<'p> for $name<'p> {
+            /// Panics if the type object cannot be created; see `try_type_object()`.
+            fn type_object(py: $crate::Python<'p>) -> $crate::PyType<'p> {
+                match $name::try_type_object(py) {
+                    Ok(t) => t,
+                    Err(e) => panic!(concat!("Failed to create exception type ", stringify!($name), ": {}"), e)
+                }
+            }
+        }
+
+        impl <'p> $name<'p> {
+            /// Gets the python type object, creating it on first use.
+            pub fn try_type_object(py: $crate::Python<'p>) -> $crate::PyResult<'p, $crate::PyType<'p>> {
+                use ::std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+                // Creating the type may release the GIL, so another thread might
+                // create it concurrently; the first one to be stored wins.
+                static TYPE_OBJECT: AtomicUsize = ATOMIC_USIZE_INIT;
+                let mut ptr = TYPE_OBJECT.load(Ordering::SeqCst);
+                if ptr == 0 {
+                    let t = try!($crate::PyErr::new_type(py,
+                        concat!(stringify!($module), ".", stringify!($name)),
+                        $doc, Some(py.get_type::<$base>())));
+                    // The cached pointer keeps the type object alive forever.
+                    let new_ptr = $crate::ToPythonPointer::steal_ptr(t) as usize;
+                    ptr = TYPE_OBJECT.compare_and_swap(0, new_ptr, Ordering::SeqCst);
+                    if ptr == 0 {
+                        ptr = new_ptr;
+                    } else {
+                        unsafe { $crate::_detail::ffi::Py_DECREF(new_ptr as *mut $crate::_detail::ffi::PyObject) };
+                    }
+                }
+                Ok(unsafe { $crate::PyType::from_type_ptr(py, ptr as *mut $crate::_detail::ffi::PyTypeObject) })
+            }
+
+            /// Creates a new `PyErr` of this type.
+            ///
+            /// If the type object cannot be created, the error is returned instead.
+            #[inline]
+            pub fn new<V>(py: $crate::Python<'p>, args: V) -> $crate::PyErr<'p>
+                where V: $crate::ToPyObject<'p>
+            {
+                match $name::try_type_object(py) {
+                    Ok(t) => $crate::PyErr::new_lazy_init(t,
+                        Some($crate::PythonObject::into_object($crate::ToPyObject::into_py_object(args, py)))),
+                    Err(e) => e
+                }
+            }
+        }
+    );
+}
+
 mod python;
 mod err;
 mod conversion;
 mod objects;
 mod objectprotocol;
 mod pythonrun;
+mod owned;
+mod warnings;
+mod functionclass;
+mod importer;
+mod subinterpreter;
+mod sys;
+mod interrupt;
 
 /// Private re-exports for macros. Do not use.
 #[doc(hidden)]
 pub mod _detail {
     pub use ffi;
     pub use libc;
-    pub use err::from_owned_ptr_or_panic;
+    pub use err::{from_owned_ptr_or_panic, result_from_owned_ptr};
 }
 
 /// Expands to an `extern "C"` function that allows python to load
@@ -138,7 +359,7 @@ pub mod _detail {
 ///
 /// py_module_initializer!(example, |py, m| {
 ///     try!(m.add("__doc__", "Module documentation string"));
-///     try!(m.add("run", py_func!(py, run)));
+///     try!(m.add("run", try!(try_py_func!(py, run))));
 ///     Ok(())
 /// });
 /// 
@@ -224,9 +445,20 @@ macro_rules! py_module_initializer {
 /// See `py_module_initializer!` for example usage.
 ///
 /// # Panic
-/// May panic when python runs out of memory.
+/// May panic when python runs out of memory; use `try_py_func!` to handle that case.
 #[macro_export]
 macro_rules! py_func {
+    ($py: expr, $f: expr) => ({
+        try_py_func!($py, $f).ok().expect("Failed to create python function")
+    })
+}
+
+/// Creates a python callable object that invokes a Rust function.
+///
+/// Like `py_func!`, but evaluates to a `PyResult<PyObject>`
+/// instead of panicking when the function object cannot be created.
+#[macro_export]
+macro_rules! try_py_func {
     ($py: expr, $f: expr) => ({
         unsafe extern "C" fn wrap_py_func
           (_slf: *mut $crate::_detail::ffi::PyObject, args: *mut $crate::_detail::ffi::PyObject)
@@ -252,10 +484,10 @@ macro_rules! py_func {
             ml_flags: $crate::_detail::ffi::METH_VARARGS,
             ml_doc: 0 as *const $crate::_detail::libc::c_char
         };
-        let py: Python = $py;
+        let py: $crate::Python = $py;
         unsafe {
             let obj = $crate::_detail::ffi::PyCFunction_New(&mut method_def, ::std::ptr::null_mut());
-            $crate::_detail::from_owned_ptr_or_panic(py, obj)
+            $crate::_detail::result_from_owned_ptr(py, obj)
         }
     })
 }
@@ -23,8 +23,8 @@ use std::cmp::Ordering;
 use ffi;
 use libc;
 use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, ToPythonPointer};
-use objects::{PyObject, PyTuple, PyDict, PyString};
-use conversion::ToPyObject;
+use objects::{PyObject, PyTuple, PyDict, PyString, PyIterator, PyTypedIterator};
+use conversion::{ToPyObject, FromPyObject};
 use err::{PyErr, PyResult, result_from_owned_ptr, error_on_minusone};
 
 /// Trait that contains methods 
@@ -216,37 +216,28 @@ pub trait ObjectProtocol<'p> : PythonObject<'p> {
                 ffi::PyObject_DelItem(self.as_ptr(), key))
         })
     }
-    /*
+
     /// Takes an object and returns an iterator for it.
     /// This is typically a new iterator but if the argument
     /// is an iterator, this returns itself.
+    /// This is equivalent to the python expression 'iter(self)'.
     #[inline]
-    fn iter(&self) -> PyResult<'p, PyPtr<'p, PyIterator<'p>>> {
+    fn iter(&self) -> PyResult<'p, PyIterator<'p>> {
         let it = try!(unsafe {
             result_from_owned_ptr(self.python(), ffi::PyObject_GetIter(self.as_ptr()))
         });
-        it.downcast_into()
-    }*/
-}
-
-impl <'p> ObjectProtocol<'p> for PyObject<'p> {}
-
-/*
-pub struct PyIterator<'p>(PyObject<'p>);
-
-impl <'p> PythonObject<'p> for PyIterator<'p> {
-    #[inline]
-    fn as_object<'a>(&'a self) -> &'a PyObject<'p> {
-        &self.0
+        // PyObject_GetIter() raises TypeError if the result is not an iterator
+        Ok(unsafe { it.unchecked_cast_into::<PyIterator>() })
     }
-    
+
+    /// Takes an object and returns an iterator that extracts each of its items as `T`.
     #[inline]
-    unsafe fn unchecked_downcast_from<'a>(o: &'a PyObject<'p>) -> &'a PyIterator<'p> {
-        std::mem::transmute(o)
+    fn iter_as<T>(&self) -> PyResult<'p, PyTypedIterator<'p, T>> where T: FromPyObject<'p> {
+        Ok(PyTypedIterator::new(try!(self.iter())))
     }
 }
 
-*/
+impl <'p> ObjectProtocol<'p> for PyObject<'p> {}
 
 impl <'p> fmt::Debug for PyObject<'p> {
     fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
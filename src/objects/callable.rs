@@ -0,0 +1,285 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std;
+use std::ffi::CString;
+use std::thread;
+use libc::{c_int, c_void};
+use ffi;
+use python::{Python, PythonObject, ToPythonPointer};
+use conversion::{ToPyObject, FromPyObject};
+use objects::{exc, PyObject, PyTuple, PyDict};
+use err::{self, PyResult, ToPyErr};
+
+pyobject_newtype!(PyCallable, PyCallable_Check, @name "callable");
+
+impl <'p> PyCallable<'p> {
+    /// Creates a python callable object that invokes a Rust closure.
+    ///
+    /// Unlike `py_func!`, the closure may capture state.
+    /// The closure is dropped when the python object is garbage collected.
+    ///
+    /// The closure must be `Send` and `Sync` because python may call it from any thread,
+    /// and from multiple threads if the closure releases the GIL.
+    pub fn from_closure<F>(py: Python<'p>, name: &str, f: F) -> PyResult<'p, PyCallable<'p>>
+        where F: for<'a> Fn(Python<'a>, &PyTuple<'a>, Option<&PyDict<'a>>) -> PyResult<'a, PyObject<'a>>,
+              F: Send + Sync + 'static
+    {
+        unsafe {
+            let meth: ffi::PyCFunctionWithKeywords = call_rust_closure::<F>;
+            let (function, _) = try!(new_rust_callable(py, name, std::mem::transmute(meth),
+                ffi::METH_VARARGS | ffi::METH_KEYWORDS, f));
+            Ok(function.unchecked_cast_into::<PyCallable>())
+        }
+    }
+
+    /// Calls the object without arguments.
+    /// This is equivalent to the python expression: 'self()'
+    pub fn call0(&self) -> PyResult<'p, PyObject<'p>> {
+        self.call1(try!(PyTuple::try_empty(self.python())))
+    }
+
+    /// Calls the object with positional arguments.
+    /// This is equivalent to the python expression: 'self(*args)'
+    ///
+    /// `args` is usually a Rust tuple, e.g. `f.call1((1, "a"))`.
+    pub fn call1<A>(&self, args: A) -> PyResult<'p, PyObject<'p>>
+        where A: ToPyObject<'p, ObjectType=PyTuple<'p>>
+    {
+        let py = self.python();
+        args.with_borrowed_ptr(py, |args| unsafe {
+            err::result_from_owned_ptr(py, ffi::PyObject_Call(self.as_ptr(), args, ::std::ptr::null_mut()))
+        })
+    }
+
+    /// Calls the object with positional and keyword arguments.
+    /// This is equivalent to the python expression: 'self(*args, **kwargs)'
+    ///
+    /// The keyword arguments are usually constructed using the `kwargs!` macro:
+    /// `f.call_kw(NoArgs, kwargs!(x = 1))`.
+    pub fn call_kw<A, K>(&self, args: A, kwargs: K) -> PyResult<'p, PyObject<'p>>
+        where A: ToPyObject<'p, ObjectType=PyTuple<'p>>, K: KeywordArgs<'p>
+    {
+        let py = self.python();
+        let kwargs = try!(kwargs.into_kwargs_dict(py));
+        args.with_borrowed_ptr(py, |args| unsafe {
+            err::result_from_owned_ptr(py, ffi::PyObject_Call(self.as_ptr(), args, kwargs.as_ptr()))
+        })
+    }
+
+    /// Calls the object with positional arguments and extracts the return value as `R`.
+    pub fn call1_as<R, A>(&self, args: A) -> PyResult<'p, R>
+        where A: ToPyObject<'p, ObjectType=PyTuple<'p>>, R: FromPyObject<'p>
+    {
+        try!(self.call1(args)).extract::<R>()
+    }
+
+    /// Calls the object with positional and keyword arguments and extracts the return value as `R`.
+    pub fn call_kw_as<R, A, K>(&self, args: A, kwargs: K) -> PyResult<'p, R>
+        where A: ToPyObject<'p, ObjectType=PyTuple<'p>>, K: KeywordArgs<'p>, R: FromPyObject<'p>
+    {
+        try!(self.call_kw(args, kwargs)).extract::<R>()
+    }
+}
+
+/// Trait for values that can be passed as keyword arguments to `PyCallable::call_kw()`.
+///
+/// Implemented by `PyDict` and by the keyword lists constructed by the `kwargs!` macro.
+pub trait KeywordArgs<'p> : Sized {
+    /// Adds the keyword arguments to the dictionary.
+    fn add_to_dict(self, dict: &PyDict<'p>) -> PyResult<'p, ()>;
+
+    /// Converts the keyword arguments into a dictionary.
+    fn into_kwargs_dict(self, py: Python<'p>) -> PyResult<'p, PyDict<'p>> {
+        let dict = PyDict::new(py);
+        try!(self.add_to_dict(&dict));
+        Ok(dict)
+    }
+}
+
+impl <'p> KeywordArgs<'p> for PyDict<'p> {
+    fn add_to_dict(self, dict: &PyDict<'p>) -> PyResult<'p, ()> {
+        dict.update(self.as_object())
+    }
+
+    #[inline]
+    fn into_kwargs_dict(self, _py: Python<'p>) -> PyResult<'p, PyDict<'p>> {
+        Ok(self)
+    }
+}
+
+impl <'a, 'p> KeywordArgs<'p> for &'a PyDict<'p> {
+    fn add_to_dict(self, dict: &PyDict<'p>) -> PyResult<'p, ()> {
+        dict.update(self.as_object())
+    }
+
+    #[inline]
+    fn into_kwargs_dict(self, _py: Python<'p>) -> PyResult<'p, PyDict<'p>> {
+        Ok(self.clone())
+    }
+}
+
+/// The empty keyword list.
+impl <'p> KeywordArgs<'p> for () {
+    #[inline]
+    fn add_to_dict(self, _dict: &PyDict<'p>) -> PyResult<'p, ()> {
+        Ok(())
+    }
+}
+
+/// A keyword argument with name and value, followed by the remaining keyword arguments.
+/// Constructed by the `kwargs!` macro.
+pub struct KwArg<V, R>(pub &'static str, pub V, pub R);
+
+impl <'p, V, R> KeywordArgs<'p> for KwArg<V, R> where V: ToPyObject<'p>, R: KeywordArgs<'p> {
+    fn add_to_dict(self, dict: &PyDict<'p>) -> PyResult<'p, ()> {
+        let KwArg(name, value, rest) = self;
+        try!(dict.set_item(name, value));
+        rest.add_to_dict(dict)
+    }
+}
+
+const RUST_CALLABLE_CAPSULE: &'static [u8] = b"rust_callable\0";
+
+struct RustCallable<S> {
+    // Must stay at a stable address for the lifetime of the PyCFunction,
+    // which is guaranteed because the capsule owning this struct is its 'self'.
+    method_def: ffi::PyMethodDef,
+    name: CString,
+    state: S
+}
+
+/// Creates a python function object that invokes `meth`, passing a capsule owning `state` as 'self'.
+/// Returns the function object and the capsule.
+///
+/// `meth` can retrieve the state using `rust_callable_state::<S>(slf)`.
+/// The state is dropped when the function object is garbage collected.
+pub unsafe fn new_rust_callable<'p, S>(py: Python<'p>, name: &str, meth: ffi::PyCFunction, flags: c_int, state: S)
+    -> PyResult<'p, (PyObject<'p>, PyObject<'p>)> where S: Send + 'static
+{
+    let name = try!(CString::new(name).map_err(|e| e.to_py_err(py)));
+    let mut callable = Box::new(RustCallable {
+        method_def: ffi::PyMethodDef {
+            ml_name: std::ptr::null(),
+            ml_meth: Some(meth),
+            ml_flags: flags,
+            ml_doc: std::ptr::null()
+        },
+        name: name,
+        state: state
+    });
+    callable.method_def.ml_name = callable.name.as_ptr();
+    let callable = Box::into_raw(callable);
+    let capsule = match err::result_from_owned_ptr(py,
+        ffi::PyCapsule_New(callable as *mut c_void, RUST_CALLABLE_CAPSULE.as_ptr() as *const _,
+            Some(drop_rust_callable::<S>)))
+    {
+        Ok(capsule) => capsule,
+        Err(e) => {
+            drop(Box::from_raw(callable));
+            return Err(e);
+        }
+    };
+    let function = try!(err::result_from_owned_ptr(py,
+        ffi::PyCFunction_New(&mut (*callable).method_def, capsule.as_ptr())));
+    Ok((function, capsule))
+}
+
+/// Retrieves the state of a function object created by `new_rust_callable()`.
+/// Undefined behavior if `slf` is not the capsule created for state type `S`.
+pub unsafe fn rust_callable_state<'a, S>(slf: *mut ffi::PyObject) -> &'a S {
+    let callable = ffi::PyCapsule_GetPointer(slf, RUST_CALLABLE_CAPSULE.as_ptr() as *const _) as *const RustCallable<S>;
+    &(*callable).state
+}
+
+unsafe extern "C" fn drop_rust_callable<S>(capsule: *mut ffi::PyObject) {
+    let callable = ffi::PyCapsule_GetPointer(capsule, RUST_CALLABLE_CAPSULE.as_ptr() as *const _) as *mut RustCallable<S>;
+    drop(Box::from_raw(callable));
+}
+
+unsafe extern "C" fn call_rust_closure<F>(slf: *mut ffi::PyObject, args: *mut ffi::PyObject, kwargs: *mut ffi::PyObject)
+    -> *mut ffi::PyObject
+    where F: for<'a> Fn(Python<'a>, &PyTuple<'a>, Option<&PyDict<'a>>) -> PyResult<'a, PyObject<'a>>,
+          F: Send + Sync + 'static
+{
+    let (slf_addr, args_addr, kwargs_addr) = (slf as usize, args as usize, kwargs as usize);
+    // Unwinding into the python interpreter is undefined behavior.
+    let result = thread::catch_panic(move || {
+        let py = Python::assume_gil_acquired();
+        let f = rust_callable_state::<F>(slf_addr as *mut ffi::PyObject);
+        let args = PyObject::from_borrowed_ptr(py, args_addr as *mut ffi::PyObject);
+        let kwargs = PyObject::from_borrowed_ptr_opt(py, kwargs_addr as *mut ffi::PyObject);
+        match f(py, args.unchecked_cast_as::<PyTuple>(), kwargs.as_ref().map(|k| k.unchecked_cast_as::<PyDict>())) {
+            Ok(val) => val.steal_ptr(),
+            Err(e) => {
+                e.restore();
+                std::ptr::null_mut()
+            }
+        }
+    });
+    match result {
+        Ok(ptr) => ptr,
+        Err(_) => {
+            let py = Python::assume_gil_acquired();
+            py_err!(py, exc::SystemError, "panic in rust closure").restore();
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::{exc, PyCallable};
+
+    #[test]
+    fn test_call() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let builtins = py.import("__builtin__").or_else(|_| py.import("builtins")).unwrap();
+        let max = builtins.get("max").unwrap().cast_into::<PyCallable>().unwrap();
+        assert_eq!(3, max.call1_as::<i32, _>((1, 3, 2)).unwrap());
+        let sorted = builtins.get("sorted").unwrap().cast_into::<PyCallable>().unwrap();
+        let list = [1, 3, 2].to_py_object(py).into_object();
+        let v = sorted.call_kw_as::<Vec<i32>, _, _>((list,), kwargs!(reverse = true)).unwrap();
+        assert_eq!(vec![3, 2, 1], v);
+        assert!(py.None().cast_into::<PyCallable>().is_err());
+    }
+
+    #[test]
+    fn test_from_closure() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let offset = 10;
+        let f = PyCallable::from_closure(py, "add_offset", move |py, args, _| {
+            let v = try!(args.get_item(0).extract::<i32>());
+            Ok((v + offset).to_py_object(py).into_object())
+        }).unwrap();
+        assert_eq!(15, f.call1_as::<i32, _>((5,)).unwrap());
+    }
+
+    #[test]
+    fn test_from_closure_panic() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let f = PyCallable::from_closure(py, "panics", |_, _, _| panic!("closure panic")).unwrap();
+        let err = f.call0().unwrap_err();
+        assert!(err.matches(py.get_type::<exc::SystemError>().as_object()));
+    }
+}
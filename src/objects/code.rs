@@ -0,0 +1,259 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::{error, fmt};
+use std::ffi::CString;
+use ffi;
+use libc::c_int;
+use python::{Python, PythonObject, ToPythonPointer};
+use objectprotocol::ObjectProtocol;
+use objects::{PyObject, PyDict, PyBytes, exc};
+use err::{self, PyErr, PyResult, ToPyErr};
+
+#[cfg(feature="python27-sys")]
+pyobject_newtype!(PyCode, PyCode_Check, PyCode_Type);
+
+// The stable ABI used for python 3 does not expose the code object type,
+// so the type check goes through `types.CodeType`.
+#[cfg(feature="python3-sys")]
+pyobject_newtype!(PyCode);
+
+#[cfg(feature="python3-sys")]
+fn is_code_object<'p>(obj: &PyObject<'p>) -> bool {
+    match obj.python().import("types").and_then(|types| types.get("CodeType")) {
+        Ok(code_type) => code_type.as_ptr() == obj.get_type().as_ptr(),
+        Err(_) => false
+    }
+}
+
+#[cfg(feature="python3-sys")]
+impl <'p> ::python::PythonObjectWithCheckedDowncast<'p> for PyCode<'p> {
+    fn downcast_from(obj: PyObject<'p>) -> Result<PyCode<'p>, ::python::PythonObjectDowncastError<'p>> {
+        if is_code_object(&obj) {
+            Ok(PyCode(obj))
+        } else {
+            Err(::python::PythonObjectDowncastError::new_with_name("code", &obj))
+        }
+    }
+
+    fn downcast_borrow_from<'a>(obj: &'a PyObject<'p>) -> Result<&'a PyCode<'p>, ::python::PythonObjectDowncastError<'p>> {
+        if is_code_object(obj) {
+            Ok(unsafe { PythonObject::unchecked_downcast_borrow_from(obj) })
+        } else {
+            Err(::python::PythonObjectDowncastError::new_with_name("code", obj))
+        }
+    }
+}
+
+/// Specifies what kind of source code is compiled by `Python::compile()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompileMode {
+    /// A single expression; running the code returns its value (like `eval()`).
+    Eval,
+    /// A sequence of statements, e.g. a module (like `exec`).
+    Exec,
+    /// A single interactive statement; expression values are printed.
+    Single
+}
+
+impl CompileMode {
+    fn start_symbol(self) -> c_int {
+        match self {
+            CompileMode::Eval => ffi::Py_eval_input,
+            CompileMode::Exec => ffi::Py_file_input,
+            CompileMode::Single => ffi::Py_single_input
+        }
+    }
+}
+
+/// Error returned by `Python::compile()`.
+pub enum CompileError<'p> {
+    /// The source code is invalid.
+    /// Use the accessors of `SyntaxError` to get the location of the error.
+    Syntax(exc::SyntaxError<'p>),
+    /// Any other error, e.g. a NUL byte in the source code.
+    Other(PyErr<'p>)
+}
+
+impl <'p> CompileError<'p> {
+    fn from_err(mut err: PyErr<'p>) -> CompileError<'p> {
+        let py = err.instance().python();
+        if err.matches(py.get_type::<exc::SyntaxError>().as_object()) {
+            if let Ok(syntax_error) = err.instance().cast_into::<exc::SyntaxError>() {
+                return CompileError::Syntax(syntax_error);
+            }
+        }
+        CompileError::Other(err)
+    }
+}
+
+impl <'p> fmt::Debug for CompileError<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl <'p> fmt::Display for CompileError<'p> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            // str() of a SyntaxError includes the file name and line number
+            CompileError::Syntax(ref e) => write!(f, "SyntaxError: {:?}", e.as_object()),
+            CompileError::Other(ref e) => fmt::Display::fmt(e, f)
+        }
+    }
+}
+
+impl <'p> error::Error for CompileError<'p> {
+    fn description(&self) -> &str {
+        "failed to compile python code"
+    }
+}
+
+/// Converts the `CompileError` back into the python exception.
+impl <'p> From<CompileError<'p>> for PyErr<'p> {
+    fn from(err: CompileError<'p>) -> PyErr<'p> {
+        match err {
+            CompileError::Syntax(e) => PyErr::from_instance(e),
+            CompileError::Other(e) => e
+        }
+    }
+}
+
+/// Rust strings are UTF-8, which python 2 does not assume for unicode literals by default.
+#[cfg(feature="python27-sys")]
+unsafe fn compile_string(source: &CString, filename: &CString, mode: CompileMode) -> *mut ffi::PyObject {
+    let mut flags = ffi::PyCompilerFlags { cf_flags: ffi::PyCF_SOURCE_IS_UTF8 };
+    ffi::Py_CompileStringFlags(source.as_ptr(), filename.as_ptr(), mode.start_symbol(), &mut flags)
+}
+
+/// Python 3 decodes source code as UTF-8 by default.
+#[cfg(feature="python3-sys")]
+unsafe fn compile_string(source: &CString, filename: &CString, mode: CompileMode) -> *mut ffi::PyObject {
+    ffi::Py_CompileString(source.as_ptr(), filename.as_ptr(), mode.start_symbol())
+}
+
+impl <'p> PyCode<'p> {
+    /// Compiles python source code. See `Python::compile()`.
+    pub fn compile(py: Python<'p>, source: &str, filename: &str, mode: CompileMode) -> Result<PyCode<'p>, CompileError<'p>> {
+        let source = try!(CString::new(source).map_err(|e| CompileError::Other(e.to_py_err(py))));
+        let filename = try!(CString::new(filename).map_err(|e| CompileError::Other(e.to_py_err(py))));
+        unsafe {
+            err::result_cast_from_owned_ptr(py, compile_string(&source, &filename, mode))
+        }.map_err(CompileError::from_err)
+    }
+
+    /// Executes the code object.
+    ///
+    /// If `globals` is `None`, a new empty namespace is used.
+    /// If `locals` is `None`, it defaults to `globals`.
+    /// Returns the value of the expression for code compiled with `CompileMode::Eval`,
+    /// and `None` otherwise.
+    pub fn run(&self, globals: Option<&PyDict<'p>>, locals: Option<&PyDict<'p>>) -> PyResult<'p, PyObject<'p>> {
+        let py = self.python();
+        let new_globals;
+        let globals = match globals {
+            Some(globals) => globals,
+            None => { new_globals = PyDict::new(py); &new_globals }
+        };
+        let locals = locals.unwrap_or(globals);
+        // Like the builtin eval(), make the builtins available to the executed code.
+        if globals.get_item("__builtins__").is_none() {
+            let builtins = unsafe { PyObject::from_borrowed_ptr(py, ffi::PyEval_GetBuiltins()) };
+            try!(globals.set_item("__builtins__", builtins));
+        }
+        unsafe {
+            err::result_from_owned_ptr(py,
+                ffi::PyEval_EvalCode(self.as_ptr() as *mut _, globals.as_ptr(), locals.as_ptr()))
+        }
+    }
+
+    /// Serializes the code object using the `marshal` module.
+    ///
+    /// The format is specific to the python version; it can only be loaded
+    /// by `PyCode::from_bytes()` running on the same python version.
+    pub fn to_bytes(&self) -> PyResult<'p, Vec<u8>> {
+        let py = self.python();
+        let marshal = try!(py.import("marshal"));
+        let bytes = try!(try!(marshal.as_object().call_method("dumps", &(self.as_object(),), None)).cast_into::<PyBytes>());
+        Ok(bytes.as_slice().to_vec())
+    }
+
+    /// Loads a code object serialized by `PyCode::to_bytes()`.
+    pub fn from_bytes(py: Python<'p>, data: &[u8]) -> PyResult<'p, PyCode<'p>> {
+        let marshal = try!(py.import("marshal"));
+        let obj = try!(marshal.as_object().call_method("loads", &(PyBytes::new(py, data).into_object(),), None));
+        Ok(try!(obj.cast_into::<PyCode>()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objects::{PyDict, exc};
+    use super::{PyCode, CompileMode, CompileError};
+
+    #[test]
+    fn test_compile_and_run() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let code = py.compile("x * 2", "<rule>", CompileMode::Eval).unwrap();
+        for i in 0..3 {
+            let globals = PyDict::new(py);
+            globals.set_item("x", i).unwrap();
+            assert_eq!(code.run(Some(&globals), None).unwrap().extract::<i32>().unwrap(), i * 2);
+        }
+    }
+
+    #[test]
+    fn test_compile_non_ascii() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let code = py.compile("u'\u{e4}\u{20ac}'", "<string>", CompileMode::Eval).unwrap();
+        assert_eq!(code.run(None, None).unwrap().extract::<String>().unwrap(), "\u{e4}\u{20ac}");
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        match py.compile("x = 1\ny = (", "rule.py", CompileMode::Exec) {
+            Err(CompileError::Syntax(e)) => {
+                assert_eq!(e.filename(), Some("rule.py".to_owned()));
+                assert_eq!(e.lineno(), Some(2));
+            }
+            Err(CompileError::Other(_)) => panic!("expected SyntaxError"),
+            Ok(_) => panic!("expected an error")
+        }
+        let err: ::err::PyErr = py.compile("1 +", "<rule>", CompileMode::Eval).err().unwrap().into();
+        assert!(err.matches(py.get_type::<exc::SyntaxError>().as_object()));
+    }
+
+    #[test]
+    fn test_marshal() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let code = py.compile("a + b", "<rule>", CompileMode::Eval).unwrap();
+        let data = code.to_bytes().unwrap();
+        let loaded = PyCode::from_bytes(py, &data).unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("a", 1).unwrap();
+        globals.set_item("b", 2).unwrap();
+        assert_eq!(loaded.run(Some(&globals), None).unwrap().extract::<i32>().unwrap(), 3);
+        assert!(PyCode::from_bytes(py, b"\x00").is_err());
+    }
+}
@@ -17,10 +17,13 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std;
-use ffi;
+use std::collections::{HashMap, BTreeMap};
+use std::hash::Hash;
+use libc::c_int;
+use ffi::{self, Py_ssize_t};
 use python::{Python, ToPythonPointer, PythonObject};
-use conversion::ToPyObject;
-use objects::PyObject;
+use conversion::{ToPyObject, FromPyObject};
+use objects::{PyObject, PyList};
 use err::{self, PyResult, PyErr};
 
 pyobject_newtype!(PyDict, PyDict_Check, PyDict_Type);
@@ -29,13 +32,34 @@ impl <'p> PyDict<'p> {
     /// Creates a new empty dictionary.
     ///
     /// # Panic
-    /// May panic when running out of memory.
+    /// May panic when running out of memory; use `try_new()` to handle that case.
     pub fn new(py: Python<'p>) -> PyDict<'p> {
         unsafe {
             err::cast_from_owned_ptr_or_panic(py, ffi::PyDict_New())
         }
     }
 
+    /// Creates a new empty dictionary.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_new(py: Python<'p>) -> PyResult<'p, PyDict<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyDict_New())
+        }
+    }
+
+    /// Creates a new dictionary from a sequence of key-value pairs.
+    /// `seq` must be an iterable object producing iterable objects of length 2;
+    /// later pairs override earlier pairs with the same key.
+    /// This is equivalent to the python expression `dict(seq)`.
+    pub fn from_sequence(py: Python<'p>, seq: &PyObject<'p>) -> PyResult<'p, PyDict<'p>> {
+        let dict = try!(PyDict::try_new(py));
+        unsafe {
+            try!(err::error_on_minusone(py,
+                ffi::PyDict_MergeFromSeq2(dict.as_ptr(), seq.as_ptr(), 1)));
+        }
+        Ok(dict)
+    }
+
     /// Return a new dictionary that contains the same key-value pairs as self.
     pub fn copy(&self) -> PyResult<'p, PyDict<'p>> {
         let py = self.python();
@@ -78,6 +102,41 @@ impl <'p> PyDict<'p> {
         })
     }
 
+    /// Gets an item from the dictionary.
+    /// Returns `Ok(None)` if the item is not present, and `Err` if the lookup
+    /// raised an exception (e.g. because the key is not hashable).
+    #[cfg(feature="python3-sys")]
+    pub fn get_item_with_error<K>(&self, key: K) -> PyResult<'p, Option<PyObject<'p>>> where K: ToPyObject<'p> {
+        let py = self.python();
+        key.with_borrowed_ptr(py, |key| unsafe {
+            match PyObject::from_borrowed_ptr_opt(py,
+                ffi::PyDict_GetItemWithError(self.as_ptr(), key))
+            {
+                Some(obj) => Ok(Some(obj)),
+                None if PyErr::occurred(py) => Err(PyErr::fetch(py)),
+                None => Ok(None)
+            }
+        })
+    }
+
+    /// Gets an item from the dictionary.
+    /// Returns `Ok(None)` if the item is not present, and `Err` if the lookup
+    /// raised an exception (e.g. because the key is not hashable).
+    #[cfg(feature="python27-sys")]
+    pub fn get_item_with_error<K>(&self, key: K) -> PyResult<'p, Option<PyObject<'p>>> where K: ToPyObject<'p> {
+        // Python 2.7 lacks PyDict_GetItemWithError(), and PyDict_GetItem()
+        // swallows errors. PyDict_Contains() reports them, so use it first.
+        let py = self.python();
+        key.with_borrowed_ptr(py, |key| unsafe {
+            match ffi::PyDict_Contains(self.as_ptr(), key) {
+                1 => Ok(PyObject::from_borrowed_ptr_opt(py,
+                        ffi::PyDict_GetItem(self.as_ptr(), key))),
+                0 => Ok(None),
+                _ => Err(PyErr::fetch(py))
+            }
+        })
+    }
+
     /// Sets an item value.
     /// This is equivalent to the Python expression `self[key] = value`.
     pub fn set_item<K, V>(&self, key: K, value: V) -> PyResult<'p, ()> where K: ToPyObject<'p>, V: ToPyObject<'p> {
@@ -89,6 +148,21 @@ impl <'p> PyDict<'p> {
             }))
     }
 
+    /// Gets the value for `key`, inserting `default` first if the key is not present.
+    /// This is equivalent to the Python expression `self.setdefault(key, default)`.
+    pub fn set_default<K, V>(&self, key: K, default: V) -> PyResult<'p, PyObject<'p>> where K: ToPyObject<'p>, V: ToPyObject<'p> {
+        let py = self.python();
+        let key = key.into_py_object(py).into_object();
+        match try!(self.get_item_with_error(&key)) {
+            Some(value) => Ok(value),
+            None => {
+                let default = default.into_py_object(py).into_object();
+                try!(self.set_item(&key, &default));
+                Ok(default)
+            }
+        }
+    }
+
     /// Deletes an item.
     /// This is equivalent to the Python expression 'del self[key]'.
     pub fn del_item<K>(&self, key: K) -> PyResult<'p, ()> where K: ToPyObject<'p> {
@@ -98,5 +172,216 @@ impl <'p> PyDict<'p> {
                 ffi::PyDict_DelItem(self.as_ptr(), key))
         })
     }
+
+    /// Updates the dictionary with the key-value pairs from `other`,
+    /// which may be a dict or any other object supporting `keys()` and `__getitem__`.
+    /// This is equivalent to the Python expression `self.update(other)`.
+    pub fn update(&self, other: &PyObject<'p>) -> PyResult<'p, ()> {
+        let py = self.python();
+        unsafe {
+            err::error_on_minusone(py,
+                ffi::PyDict_Update(self.as_ptr(), other.as_ptr()))
+        }
+    }
+
+    /// Merges the key-value pairs from the mapping `other` into the dictionary.
+    /// If `override_existing` is false, keys already present in the dictionary are left unchanged.
+    pub fn merge(&self, other: &PyObject<'p>, override_existing: bool) -> PyResult<'p, ()> {
+        let py = self.python();
+        unsafe {
+            err::error_on_minusone(py,
+                ffi::PyDict_Merge(self.as_ptr(), other.as_ptr(), override_existing as c_int))
+        }
+    }
+
+    /// Returns a new list containing the keys of the dictionary.
+    ///
+    /// # Panic
+    /// May panic when running out of memory; use `try_keys()` to handle that case.
+    pub fn keys(&self) -> PyList<'p> {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(self.python(), ffi::PyDict_Keys(self.as_ptr()))
+        }
+    }
+
+    /// Returns a new list containing the keys of the dictionary.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_keys(&self) -> PyResult<'p, PyList<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(self.python(), ffi::PyDict_Keys(self.as_ptr()))
+        }
+    }
+
+    /// Returns a new list containing the values of the dictionary.
+    ///
+    /// # Panic
+    /// May panic when running out of memory; use `try_values()` to handle that case.
+    pub fn values(&self) -> PyList<'p> {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(self.python(), ffi::PyDict_Values(self.as_ptr()))
+        }
+    }
+
+    /// Returns a new list containing the values of the dictionary.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_values(&self) -> PyResult<'p, PyList<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(self.python(), ffi::PyDict_Values(self.as_ptr()))
+        }
+    }
+
+    /// Returns a new list containing the `(key, value)` tuples of the dictionary.
+    ///
+    /// # Panic
+    /// May panic when running out of memory; use `try_items()` to handle that case.
+    pub fn items(&self) -> PyList<'p> {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(self.python(), ffi::PyDict_Items(self.as_ptr()))
+        }
+    }
+
+    /// Returns a new list containing the `(key, value)` tuples of the dictionary.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_items(&self) -> PyResult<'p, PyList<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(self.python(), ffi::PyDict_Items(self.as_ptr()))
+        }
+    }
+}
+
+impl <'p> IntoIterator for PyDict<'p> {
+    type Item = (PyObject<'p>, PyObject<'p>);
+    type IntoIter = PyDictIterator<'p>;
+
+    #[inline]
+    fn into_iter(self) -> PyDictIterator<'p> {
+        PyDictIterator { dict: self, pos: 0 }
+    }
+}
+
+impl <'a, 'p> IntoIterator for &'a PyDict<'p> {
+    type Item = (PyObject<'p>, PyObject<'p>);
+    type IntoIter = PyDictIterator<'p>;
+
+    #[inline]
+    fn into_iter(self) -> PyDictIterator<'p> {
+        PyDictIterator { dict: self.clone(), pos: 0 }
+    }
+}
+
+/// Iterator over the `(key, value)` pairs of a dictionary.
+///
+/// The dictionary must not be resized (items added or removed) during the iteration;
+/// python will silently skip or repeat entries if this happens.
+pub struct PyDictIterator<'p> {
+    dict: PyDict<'p>,
+    pos: Py_ssize_t
+}
+
+impl <'p> Iterator for PyDictIterator<'p> {
+    type Item = (PyObject<'p>, PyObject<'p>);
+
+    #[inline]
+    fn next(&mut self) -> Option<(PyObject<'p>, PyObject<'p>)> {
+        let py = self.dict.python();
+        let mut key: *mut ffi::PyObject = std::ptr::null_mut();
+        let mut value: *mut ffi::PyObject = std::ptr::null_mut();
+        unsafe {
+            if ffi::PyDict_Next(self.dict.as_ptr(), &mut self.pos, &mut key, &mut value) != 0 {
+                Some((PyObject::from_borrowed_ptr(py, key), PyObject::from_borrowed_ptr(py, value)))
+            } else {
+                None
+            }
+        }
+    }
 }
 
+impl <'p, K, V> FromPyObject<'p> for HashMap<K, V>
+    where K: FromPyObject<'p> + Eq + Hash, V: FromPyObject<'p>
+{
+    fn from_py_object(s: &PyObject<'p>) -> PyResult<'p, HashMap<K, V>> {
+        let dict = try!(s.cast_as::<PyDict>());
+        let mut map = HashMap::with_capacity(dict.len());
+        for (key, value) in dict {
+            map.insert(try!(key.extract::<K>()), try!(value.extract::<V>()));
+        }
+        Ok(map)
+    }
+}
+
+impl <'p, K, V> FromPyObject<'p> for BTreeMap<K, V>
+    where K: FromPyObject<'p> + Ord, V: FromPyObject<'p>
+{
+    fn from_py_object(s: &PyObject<'p>) -> PyResult<'p, BTreeMap<K, V>> {
+        let dict = try!(s.cast_as::<PyDict>());
+        let mut map = BTreeMap::new();
+        for (key, value) in dict {
+            map.insert(try!(key.extract::<K>()), try!(value.extract::<V>()));
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::PyDict;
+
+    #[test]
+    fn test_iter() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        dict.set_item(7, 32).unwrap();
+        dict.set_item(8, 42).unwrap();
+        let mut key_sum = 0;
+        let mut value_sum = 0;
+        for (key, value) in &dict {
+            key_sum += key.extract::<i32>().unwrap();
+            value_sum += value.extract::<i32>().unwrap();
+        }
+        assert_eq!(15, key_sum);
+        assert_eq!(74, value_sum);
+        assert_eq!(2, dict.keys().len());
+        assert_eq!(2, dict.items().len());
+    }
+
+    #[test]
+    fn test_get_item_with_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        dict.set_item(7, 32).unwrap();
+        assert_eq!(32, dict.get_item_with_error(7).unwrap().unwrap().extract::<i32>().unwrap());
+        assert!(dict.get_item_with_error(8).unwrap().is_none());
+        let unhashable = PyDict::new(py);
+        assert!(dict.get_item_with_error(unhashable.as_object()).is_err());
+    }
+
+    #[test]
+    fn test_try_keys_values_items() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::try_new(py).unwrap();
+        dict.set_item(7, 32).unwrap();
+        assert_eq!(vec![7], dict.try_keys().unwrap().into_object().extract::<Vec<i32>>().unwrap());
+        assert_eq!(vec![32], dict.try_values().unwrap().into_object().extract::<Vec<i32>>().unwrap());
+        assert_eq!(1, dict.try_items().unwrap().len());
+    }
+
+    #[test]
+    fn test_set_default_and_update() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        assert_eq!(1, dict.set_default(7, 1).unwrap().extract::<i32>().unwrap());
+        assert_eq!(1, dict.set_default(7, 2).unwrap().extract::<i32>().unwrap());
+        let other = PyDict::from_sequence(py, ((8, 2),).to_py_object(py).as_object()).unwrap();
+        dict.update(other.as_object()).unwrap();
+        let map = dict.as_object().extract::<HashMap<i32, i32>>().unwrap();
+        assert_eq!(Some(&1), map.get(&7));
+        assert_eq!(Some(&2), map.get(&8));
+    }
+}
@@ -25,12 +25,25 @@ use std::mem;
 use std::ffi::CStr;
 use ffi;
 use python::{Python, ToPythonPointer, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectDowncastError, PythonObjectWithTypeObject};
-use err::{self, PyResult};
+use err::{self, PyErr, PyResult};
+use conversion::{ToPyObject, FromPyObject};
 use super::object::PyObject;
 use super::typeobject::PyType;
+use super::tuple::PyTuple;
+use super::string::PyBytes;
+use objectprotocol::ObjectProtocol;
 
 macro_rules! exc_type(
     ($name:ident, $exc_name:ident) => (
+        exc_type!(@type $name, $exc_name);
+        exc_type!(@new $name);
+        exc_type!(@args $name);
+    );
+    ($name:ident : $base:ident, $exc_name:ident) => (
+        exc_type!($name, $exc_name);
+        exc_type!(@base $name : $base);
+    );
+    (@type $name:ident, $exc_name:ident) => (
         pyobject_newtype!($name);
         
         impl <'p> PythonObjectWithCheckedDowncast<'p> for $name<'p> {
@@ -40,7 +53,7 @@ macro_rules! exc_type(
                     if ffi::PyObject_TypeCheck(obj.as_ptr(), ffi::$exc_name as *mut ffi::PyTypeObject) != 0 {
                         Ok(PythonObject::unchecked_downcast_from(obj))
                     } else {
-                        Err(PythonObjectDowncastError(obj.python()))
+                        Err(PythonObjectDowncastError::new(PyType::from_type_ptr(obj.python(), ffi::$exc_name as *mut ffi::PyTypeObject), &obj))
                     }
                 }
             }
@@ -51,7 +64,7 @@ macro_rules! exc_type(
                     if ffi::PyObject_TypeCheck(obj.as_ptr(), ffi::$exc_name as *mut ffi::PyTypeObject) != 0 {
                         Ok(PythonObject::unchecked_downcast_borrow_from(obj))
                     } else {
-                        Err(PythonObjectDowncastError(obj.python()))
+                        Err(PythonObjectDowncastError::new(PyType::from_type_ptr(obj.python(), ffi::$exc_name as *mut ffi::PyTypeObject), obj))
                     }
                 }
             }
@@ -64,48 +77,161 @@ macro_rules! exc_type(
             }
         }
     );
-    ($name:ident : $base:ident) => (
-        exc_type!($name);
+    (@new $name:ident) => (
+        impl <'p> $name<'p> {
+            /// Creates a new `PyErr` of this type.
+            ///
+            /// `args` is passed to the exception constructor;
+            /// a tuple is used as the argument list.
+            #[inline]
+            pub fn new<V>(py: Python<'p>, args: V) -> PyErr<'p> where V: ToPyObject<'p> {
+                PyErr::new::<$name, V>(py, args)
+            }
+        }
+    );
+    (@args $name:ident) => (
+        impl <'p> $name<'p> {
+            /// Gets the arguments the exception was constructed with (`args`).
+            pub fn args(&self) -> PyTuple<'p> {
+                exception_args(self.as_object())
+            }
+        }
+    );
+    (@base $name:ident : $base:ident) => (
+        /// Gives access to the methods of the python base class.
+        impl <'p> ::std::ops::Deref for $name<'p> {
+            type Target = $base<'p>;
+
+            #[inline]
+            fn deref(&self) -> &$base<'p> {
+                unsafe { PythonObject::unchecked_downcast_borrow_from(self.as_object()) }
+            }
+        }
+
+        impl <'p> From<$name<'p>> for $base<'p> {
+            #[inline]
+            fn from(e: $name<'p>) -> $base<'p> {
+                unsafe { PythonObject::unchecked_downcast_from(e.into_object()) }
+            }
+        }
     );
 );
 
 exc_type!(BaseException, PyExc_BaseException);
-exc_type!(Exception, PyExc_Exception);
+exc_type!(Exception : BaseException, PyExc_Exception);
+exc_type!(SystemExit : BaseException, PyExc_SystemExit);
+exc_type!(KeyboardInterrupt : BaseException, PyExc_KeyboardInterrupt);
+exc_type!(GeneratorExit : BaseException, PyExc_GeneratorExit);
+exc_type!(StopIteration : Exception, PyExc_StopIteration);
+#[cfg(feature="python27-sys")]
+exc_type!(StandardError : Exception, PyExc_StandardError);
+
+// The following exceptions derive from StandardError in Python 2
+// and from Exception in Python 3.
 #[cfg(feature="python27-sys")]
-exc_type!(StandardError, PyExc_StandardError);
-exc_type!(LookupError, PyExc_LookupError);
-exc_type!(AssertionError, PyExc_AssertionError);
-exc_type!(AttributeError, PyExc_AttributeError);
-exc_type!(EOFError, PyExc_EOFError);
-exc_type!(EnvironmentError, PyExc_EnvironmentError);
-exc_type!(FloatingPointError, PyExc_FloatingPointError);
-exc_type!(IOError, PyExc_IOError);
-exc_type!(ImportError, PyExc_ImportError);
-exc_type!(IndexError, PyExc_IndexError);
-exc_type!(KeyError, PyExc_KeyError);
-exc_type!(KeyboardInterrupt, PyExc_KeyboardInterrupt);
-exc_type!(MemoryError, PyExc_MemoryError);
-exc_type!(NameError, PyExc_NameError);
-exc_type!(NotImplementedError, PyExc_NotImplementedError);
-exc_type!(OSError, PyExc_OSError);
-exc_type!(OverflowError, PyExc_OverflowError);
-exc_type!(ReferenceError, PyExc_ReferenceError);
-exc_type!(RuntimeError, PyExc_RuntimeError);
-exc_type!(SyntaxError, PyExc_SyntaxError);
-exc_type!(SystemError, PyExc_SystemError);
-exc_type!(SystemExit, PyExc_SystemExit);
-exc_type!(TypeError, PyExc_TypeError);
-exc_type!(ValueError, PyExc_ValueError);
-#[cfg(target_os="windows")]
-exc_type!(WindowsError, PyExc_WindowsError);
-exc_type!(ZeroDivisionError, PyExc_ZeroDivisionError);
-
-exc_type!(UnicodeDecodeError, PyExc_UnicodeDecodeError);
-exc_type!(UnicodeEncodeError, PyExc_UnicodeEncodeError);
-exc_type!(UnicodeTranslateError, PyExc_UnicodeTranslateError);
+macro_rules! standard_exc_type(
+    ($name:ident, $exc_name:ident) => ( exc_type!($name : StandardError, $exc_name); );
+);
+#[cfg(feature="python3-sys")]
+macro_rules! standard_exc_type(
+    ($name:ident, $exc_name:ident) => ( exc_type!($name : Exception, $exc_name); );
+);
+
+standard_exc_type!(ArithmeticError, PyExc_ArithmeticError);
+standard_exc_type!(AssertionError, PyExc_AssertionError);
+standard_exc_type!(AttributeError, PyExc_AttributeError);
+standard_exc_type!(BufferError, PyExc_BufferError);
+standard_exc_type!(EOFError, PyExc_EOFError);
+standard_exc_type!(ImportError, PyExc_ImportError);
+standard_exc_type!(LookupError, PyExc_LookupError);
+standard_exc_type!(MemoryError, PyExc_MemoryError);
+standard_exc_type!(NameError, PyExc_NameError);
+standard_exc_type!(ReferenceError, PyExc_ReferenceError);
+standard_exc_type!(RuntimeError, PyExc_RuntimeError);
+standard_exc_type!(SyntaxError, PyExc_SyntaxError);
+standard_exc_type!(SystemError, PyExc_SystemError);
+standard_exc_type!(TypeError, PyExc_TypeError);
+standard_exc_type!(ValueError, PyExc_ValueError);
+
+exc_type!(FloatingPointError : ArithmeticError, PyExc_FloatingPointError);
+exc_type!(OverflowError : ArithmeticError, PyExc_OverflowError);
+exc_type!(ZeroDivisionError : ArithmeticError, PyExc_ZeroDivisionError);
+exc_type!(IndexError : LookupError, PyExc_IndexError);
+exc_type!(KeyError : LookupError, PyExc_KeyError);
+exc_type!(UnboundLocalError : NameError, PyExc_UnboundLocalError);
+exc_type!(NotImplementedError : RuntimeError, PyExc_NotImplementedError);
+exc_type!(IndentationError : SyntaxError, PyExc_IndentationError);
+exc_type!(TabError : IndentationError, PyExc_TabError);
+
+// In Python 3, EnvironmentError, IOError and WindowsError are aliases for OSError.
+#[cfg(feature="python27-sys")]
+standard_exc_type!(EnvironmentError, PyExc_EnvironmentError);
+#[cfg(feature="python27-sys")]
+exc_type!(IOError : EnvironmentError, PyExc_IOError);
+#[cfg(feature="python27-sys")]
+exc_type!(OSError : EnvironmentError, PyExc_OSError);
+#[cfg(all(feature="python27-sys", target_os="windows"))]
+exc_type!(WindowsError : OSError, PyExc_WindowsError);
+#[cfg(feature="python3-sys")]
+exc_type!(OSError : Exception, PyExc_OSError);
+#[cfg(feature="python3-sys")]
+pub type EnvironmentError<'p> = OSError<'p>;
+#[cfg(feature="python3-sys")]
+pub type IOError<'p> = OSError<'p>;
+#[cfg(all(feature="python3-sys", target_os="windows"))]
+pub type WindowsError<'p> = OSError<'p>;
+
+#[cfg(feature="python3-sys")]
+exc_type!(BlockingIOError : OSError, PyExc_BlockingIOError);
+#[cfg(feature="python3-sys")]
+exc_type!(ChildProcessError : OSError, PyExc_ChildProcessError);
+#[cfg(feature="python3-sys")]
+exc_type!(ConnectionError : OSError, PyExc_ConnectionError);
+#[cfg(feature="python3-sys")]
+exc_type!(BrokenPipeError : ConnectionError, PyExc_BrokenPipeError);
+#[cfg(feature="python3-sys")]
+exc_type!(ConnectionAbortedError : ConnectionError, PyExc_ConnectionAbortedError);
+#[cfg(feature="python3-sys")]
+exc_type!(ConnectionRefusedError : ConnectionError, PyExc_ConnectionRefusedError);
+#[cfg(feature="python3-sys")]
+exc_type!(ConnectionResetError : ConnectionError, PyExc_ConnectionResetError);
+#[cfg(feature="python3-sys")]
+exc_type!(FileExistsError : OSError, PyExc_FileExistsError);
+#[cfg(feature="python3-sys")]
+exc_type!(FileNotFoundError : OSError, PyExc_FileNotFoundError);
+#[cfg(feature="python3-sys")]
+exc_type!(InterruptedError : OSError, PyExc_InterruptedError);
+#[cfg(feature="python3-sys")]
+exc_type!(IsADirectoryError : OSError, PyExc_IsADirectoryError);
+#[cfg(feature="python3-sys")]
+exc_type!(NotADirectoryError : OSError, PyExc_NotADirectoryError);
+#[cfg(feature="python3-sys")]
+exc_type!(PermissionError : OSError, PyExc_PermissionError);
+#[cfg(feature="python3-sys")]
+exc_type!(ProcessLookupError : OSError, PyExc_ProcessLookupError);
+#[cfg(feature="python3-sys")]
+exc_type!(TimeoutError : OSError, PyExc_TimeoutError);
+
+exc_type!(UnicodeError : ValueError, PyExc_UnicodeError);
+exc_type!(UnicodeDecodeError : UnicodeError, PyExc_UnicodeDecodeError);
+exc_type!(UnicodeEncodeError : UnicodeError, PyExc_UnicodeEncodeError);
+exc_type!(UnicodeTranslateError : UnicodeError, PyExc_UnicodeTranslateError);
+
+exc_type!(Warning : Exception, PyExc_Warning);
+exc_type!(UserWarning : Warning, PyExc_UserWarning);
+exc_type!(DeprecationWarning : Warning, PyExc_DeprecationWarning);
+exc_type!(PendingDeprecationWarning : Warning, PyExc_PendingDeprecationWarning);
+exc_type!(SyntaxWarning : Warning, PyExc_SyntaxWarning);
+exc_type!(RuntimeWarning : Warning, PyExc_RuntimeWarning);
+exc_type!(FutureWarning : Warning, PyExc_FutureWarning);
+exc_type!(ImportWarning : Warning, PyExc_ImportWarning);
+exc_type!(UnicodeWarning : Warning, PyExc_UnicodeWarning);
+exc_type!(BytesWarning : Warning, PyExc_BytesWarning);
+#[cfg(feature="python3-sys")]
+exc_type!(ResourceWarning : Warning, PyExc_ResourceWarning);
 
 impl<'p> UnicodeDecodeError<'p> {
-    pub fn new(py: Python<'p>, encoding: &CStr, input: &[u8], range: Range<usize>, reason: &CStr) -> PyResult<'p, UnicodeDecodeError<'p>> {
+    pub fn create(py: Python<'p>, encoding: &CStr, input: &[u8], range: Range<usize>, reason: &CStr) -> PyResult<'p, UnicodeDecodeError<'p>> {
         unsafe {
             let input: &[c_char] = mem::transmute(input);
             err::result_cast_from_owned_ptr(py,
@@ -114,9 +240,214 @@ impl<'p> UnicodeDecodeError<'p> {
         }
     }
     
-    pub fn new_utf8(py: Python<'p>, input: &[u8], err: Utf8Error) -> PyResult<'p, UnicodeDecodeError<'p>> {
+    pub fn create_utf8(py: Python<'p>, input: &[u8], err: Utf8Error) -> PyResult<'p, UnicodeDecodeError<'p>> {
         let pos = err.valid_up_to();
-        UnicodeDecodeError::new(py, cstr!("utf-8"), input, pos .. input.len(), cstr!("invalid utf-8"))
+        UnicodeDecodeError::create(py, cstr!("utf-8"), input, pos .. input.len(), cstr!("invalid utf-8"))
+    }
+
+    /// Gets the encoding that failed.
+    pub fn encoding(&self) -> PyResult<'p, String> {
+        let py = self.python();
+        let encoding = try!(unsafe {
+            err::result_from_owned_ptr(py, ffi::PyUnicodeDecodeError_GetEncoding(self.as_ptr()))
+        });
+        encoding.extract::<String>()
+    }
+
+    /// Gets the bytes that could not be decoded.
+    pub fn object(&self) -> PyResult<'p, PyBytes<'p>> {
+        let py = self.python();
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyUnicodeDecodeError_GetObject(self.as_ptr()))
+        }
+    }
+
+    /// Gets the range of the invalid data within `object()`.
+    pub fn range(&self) -> PyResult<'p, Range<usize>> {
+        let py = self.python();
+        let mut start: ffi::Py_ssize_t = 0;
+        let mut end: ffi::Py_ssize_t = 0;
+        unsafe {
+            try!(err::error_on_minusone(py, ffi::PyUnicodeDecodeError_GetStart(self.as_ptr(), &mut start)));
+            try!(err::error_on_minusone(py, ffi::PyUnicodeDecodeError_GetEnd(self.as_ptr(), &mut end)));
+        }
+        Ok(start as usize .. end as usize)
+    }
+
+    /// Gets the description of the error.
+    pub fn reason(&self) -> PyResult<'p, String> {
+        let py = self.python();
+        let reason = try!(unsafe {
+            err::result_from_owned_ptr(py, ffi::PyUnicodeDecodeError_GetReason(self.as_ptr()))
+        });
+        reason.extract::<String>()
     }
 }
 
+fn exception_args<'p>(obj: &PyObject<'p>) -> PyTuple<'p> {
+    match obj.getattr("args").map(|args| args.cast_into::<PyTuple>()) {
+        Ok(Ok(args)) => args,
+        _ => PyTuple::new(obj.python(), &[])
+    }
+}
+
+/// Gets the attribute `name`, or `None` if it is missing, `None`, or not convertible to `T`.
+fn optional_attr<'p, T>(obj: &PyObject<'p>, name: &str) -> Option<T> where T: FromPyObject<'p> {
+    match obj.getattr(name) {
+        Ok(value) => if value == obj.python().None() { None } else { value.extract::<T>().ok() },
+        Err(_) => None
+    }
+}
+
+macro_rules! environment_error_attributes(
+    ($name:ident) => (
+        impl <'p> $name<'p> {
+            /// Gets the numeric error code from the C variable `errno`.
+            pub fn errno(&self) -> Option<i32> {
+                optional_attr(self.as_object(), "errno")
+            }
+
+            /// Gets the error message corresponding to `errno`.
+            pub fn strerror(&self) -> Option<String> {
+                optional_attr(self.as_object(), "strerror")
+            }
+
+            /// Gets the file name involved in the error.
+            pub fn filename(&self) -> Option<String> {
+                optional_attr(self.as_object(), "filename")
+            }
+        }
+    )
+);
+
+#[cfg(feature="python27-sys")]
+environment_error_attributes!(EnvironmentError);
+#[cfg(feature="python27-sys")]
+environment_error_attributes!(IOError);
+environment_error_attributes!(OSError);
+
+impl <'p> SyntaxError<'p> {
+    /// Gets the name of the file the syntax error occurred in.
+    pub fn filename(&self) -> Option<String> {
+        optional_attr(self.as_object(), "filename")
+    }
+
+    /// Gets the line number of the syntax error.
+    pub fn lineno(&self) -> Option<usize> {
+        optional_attr(self.as_object(), "lineno")
+    }
+
+    /// Gets the column of the syntax error within the line.
+    pub fn offset(&self) -> Option<usize> {
+        optional_attr(self.as_object(), "offset")
+    }
+
+    /// Gets the source code text of the line containing the error.
+    pub fn text(&self) -> Option<String> {
+        optional_attr(self.as_object(), "text")
+    }
+}
+
+impl <'p> SystemExit<'p> {
+    /// Gets the exit status or message passed to `sys.exit()`.
+    pub fn code(&self) -> PyObject<'p> {
+        let py = self.python();
+        self.as_object().getattr("code").unwrap_or(py.None())
+    }
+
+    /// Gets the process exit status the interpreter would use for this exception:
+    /// `0` for `None`, the integer for integer codes, and `1` otherwise.
+    pub fn exit_status(&self) -> i32 {
+        let code = self.code();
+        if code == self.python().None() {
+            0
+        } else {
+            code.extract::<i32>().unwrap_or(1)
+        }
+    }
+}
+
+impl <'p> KeyError<'p> {
+    /// Gets the key that was not found (`args[0]`).
+    pub fn key(&self) -> Option<PyObject<'p>> {
+        let args = self.args();
+        if args.len() > 0 { Some(args.get_item(0)) } else { None }
+    }
+}
+
+impl <'p> StopIteration<'p> {
+    /// Gets the value returned by the generator (`value` in Python 3, `args[0]` in Python 2).
+    pub fn value(&self) -> PyObject<'p> {
+        let py = self.python();
+        match self.as_object().getattr("value") {
+            Ok(value) => value,
+            Err(_) => {
+                let args = self.args();
+                if args.len() > 0 { args.get_item(0) } else { py.None() }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use err::PyErr;
+    use super::*;
+
+    #[test]
+    fn test_attributes() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut err = PyErr::new::<OSError, _>(py, (2, "No such file or directory", "x.txt"));
+        let e = err.instance().cast_into::<OSError>().unwrap();
+        assert_eq!(Some(2), e.errno());
+        assert_eq!(Some("x.txt".to_owned()), e.filename());
+        assert_eq!(3, e.args().len());
+
+        let mut err = KeyError::new(py, "missing");
+        let e = err.instance().cast_into::<KeyError>().unwrap();
+        assert_eq!("missing", e.key().unwrap().extract::<String>().unwrap());
+
+        let mut err = SystemExit::new(py, 3);
+        assert_eq!(3, err.instance().cast_into::<SystemExit>().unwrap().exit_status());
+
+        let e = UnicodeDecodeError::create(py, cstr!("utf-8"), b"ab\xff", 2..3, cstr!("invalid start byte")).unwrap();
+        assert_eq!("utf-8", e.encoding().unwrap());
+        assert_eq!(2..3, e.range().unwrap());
+        assert_eq!("invalid start byte", e.reason().unwrap());
+        assert_eq!(b"ab\xff", e.object().unwrap().as_slice());
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut err = TabError::new(py, "inconsistent use of tabs");
+        assert!(err.matches(py.get_type::<SyntaxError>().as_object()));
+        let e = err.instance().cast_into::<TabError>().unwrap();
+        let _: &SyntaxError = &**e;
+        assert!(err.instance().cast_into::<IndentationError>().is_ok());
+        assert!(err.instance().cast_into::<ValueError>().is_err());
+        #[cfg(feature="python27-sys")]
+        let _: &StandardError = &***e;
+        #[cfg(feature="python3-sys")]
+        let _: &Exception = &***e;
+
+        // Python 3.5+ raises RecursionError, a subclass of RuntimeError.
+        let err = py.run("def f(): f()\nf()", None, None).unwrap_err();
+        assert!(err.matches(py.get_type::<RuntimeError>().as_object()));
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_os_error_subclasses() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        // Python picks the subclass matching the errno when constructing the instance.
+        let mut err = PyErr::new::<OSError, _>(py, (2, "No such file or directory"));
+        let e = err.instance().cast_into::<FileNotFoundError>().unwrap();
+        assert_eq!(Some(2), e.errno());
+        assert!(err.instance().cast_into::<ConnectionError>().is_err());
+    }
+}
@@ -16,12 +16,24 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use python::{PythonObject, PythonObjectWithCheckedDowncast, ToPythonPointer};
-use objects::PyObject;
-use err::{PyErr, PyResult};
+use std;
+use std::marker::PhantomData;
+use std::cell::{Cell, UnsafeCell};
+use std::thread;
+use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, ToPythonPointer};
+use objects::{exc, PyObject};
+use super::callable;
+use conversion::{ToPyObject, FromPyObject};
+use err::{self, PyErr, PyResult};
 use ffi;
 
-pyobject_newtype!(PyIterator, PyIter_Check);
+#[cfg(feature="python27-sys")]
+pyobject_newtype!(PyIterator, PyIter_Check, @name "iterator");
+
+// PyIter_Check is not available in the limited ABI used by python3-sys,
+// so PyIterator only supports unchecked downcasts in python 3.
+#[cfg(feature="python3-sys")]
+pyobject_newtype!(PyIterator);
 
 impl <'p> PyIterator<'p> {
     /// Retrieves the next item from an iterator.
@@ -39,5 +51,196 @@ impl <'p> PyIterator<'p> {
             }
         }
     }
+
+    /// Creates a python iterator that lazily produces the items of a Rust iterator.
+    ///
+    /// Each item is converted to a python object when python requests it.
+    /// The Rust iterator is dropped once it is exhausted, or when the
+    /// python iterator object is garbage collected.
+    pub fn from_rust_iter<I, T>(py: Python<'p>, iter: I) -> PyResult<'p, PyIterator<'p>>
+        where I: Iterator<Item=T> + Send + 'static, T: for<'a> ToPyObject<'a>
+    {
+        let items: Box<RustIteratorItems> = Box::new(iter);
+        let state = RustIterator {
+            running: Cell::new(false),
+            items: UnsafeCell::new(items)
+        };
+        unsafe {
+            // The capsule serves both as 'self' of the callable and as the sentinel
+            // value that signals the end of the iteration.
+            let (callable, capsule) = try!(callable::new_rust_callable(py, "<rust iterator>",
+                rust_iterator_next, ffi::METH_NOARGS, state));
+            let iter = try!(err::result_from_owned_ptr(py,
+                ffi::PyCallIter_New(callable.as_ptr(), capsule.as_ptr())));
+            Ok(iter.unchecked_cast_into::<PyIterator>())
+        }
+    }
+}
+
+/// Iterating over a python iterator produces the items as `PyResult`,
+/// as retrieving the next item may raise a python exception.
+impl <'p> Iterator for PyIterator<'p> {
+    type Item = PyResult<'p, PyObject<'p>>;
+
+    fn next(&mut self) -> Option<PyResult<'p, PyObject<'p>>> {
+        match self.iter_next() {
+            Ok(Some(obj)) => Some(Ok(obj)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e))
+        }
+    }
+}
+
+/// Iterator that extracts each item of a python iterator as `T`.
+///
+/// Created by `ObjectProtocol::iter_as()`.
+pub struct PyTypedIterator<'p, T> {
+    iter: PyIterator<'p>,
+    phantom: PhantomData<fn() -> T>
+}
+
+impl <'p, T> PyTypedIterator<'p, T> where T: FromPyObject<'p> {
+    #[inline]
+    pub fn new(iter: PyIterator<'p>) -> PyTypedIterator<'p, T> {
+        PyTypedIterator { iter: iter, phantom: PhantomData }
+    }
+}
+
+impl <'p, T> Iterator for PyTypedIterator<'p, T> where T: FromPyObject<'p> {
+    type Item = PyResult<'p, T>;
+
+    fn next(&mut self) -> Option<PyResult<'p, T>> {
+        self.iter.next().map(|r| r.and_then(|obj| obj.extract::<T>()))
+    }
+}
+
+struct RustIterator {
+    // Guards against re-entrant calls to next(), which would alias the mutable iterator.
+    running: Cell<bool>,
+    items: UnsafeCell<Box<RustIteratorItems>>
+}
+
+/// Type-erased access to the items of a Rust iterator.
+trait RustIteratorItems : Send {
+    fn next_object<'p>(&mut self, py: Python<'p>) -> Option<PyObject<'p>>;
+}
+
+impl <I, T> RustIteratorItems for I where I: Iterator<Item=T> + Send, T: for<'a> ToPyObject<'a> {
+    fn next_object<'p>(&mut self, py: Python<'p>) -> Option<PyObject<'p>> {
+        self.next().map(|item| item.into_py_object(py).into_object())
+    }
+}
+
+/// Clears the `running` flag of a `RustIterator`, even if the Rust iterator panics.
+struct RunningGuard<'a>(&'a Cell<bool>);
+
+impl <'a> Drop for RunningGuard<'a> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+unsafe extern "C" fn rust_iterator_next(slf: *mut ffi::PyObject, _args: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    let slf_addr = slf as usize;
+    // Unwinding into the python interpreter is undefined behavior.
+    let result = thread::catch_panic(move || {
+        let slf = slf_addr as *mut ffi::PyObject;
+        let py = Python::assume_gil_acquired();
+        let state = callable::rust_callable_state::<RustIterator>(slf);
+        if state.running.get() {
+            exc::ValueError::new(py, "iterator already executing").restore();
+            return std::ptr::null_mut();
+        }
+        state.running.set(true);
+        let _guard = RunningGuard(&state.running);
+        match (*state.items.get()).next_object(py) {
+            Some(obj) => obj.steal_ptr(),
+            None => {
+                // return the sentinel
+                ffi::Py_INCREF(slf);
+                slf
+            }
+        }
+    });
+    match result {
+        Ok(ptr) => ptr,
+        Err(_) => {
+            let py = Python::assume_gil_acquired();
+            py_err!(py, exc::SystemError, "panic in rust iterator").restore();
+            std::ptr::null_mut()
+        }
+    }
 }
 
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject, ToPythonPointer};
+    use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::PyIterator;
+
+    #[test]
+    fn test_iter_as() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = [1, 2, 3].to_py_object(py).into_object();
+        let sum = list.iter_as::<i32>().unwrap().fold(0, |sum, v| sum + v.unwrap());
+        assert_eq!(6, sum);
+        assert!(py.None().iter().is_err());
+    }
+
+    #[test]
+    fn test_from_rust_iter() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let iter = PyIterator::from_rust_iter(py, (0..5).map(|i| i * i)).unwrap();
+        let v: Vec<i32> = iter.as_object().iter_as::<i32>().unwrap().map(|v| v.unwrap()).collect();
+        assert_eq!(vec![0, 1, 4, 9, 16], v);
+    }
+
+    #[test]
+    fn test_from_rust_iter_reentrant() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use objects::{PyObject, exc};
+        use ffi;
+
+        // The Rust iterator calls next() on the python iterator wrapping it.
+        // The python iterator is kept alive by the test, so a borrowed pointer suffices.
+        let slot = Arc::new(AtomicUsize::new(0));
+        let inner_slot = slot.clone();
+        let items = (0..1).map(move |i| {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            let mut iter = unsafe {
+                PyObject::from_borrowed_ptr(py, inner_slot.load(Ordering::SeqCst) as *mut ffi::PyObject)
+                    .unchecked_cast_into::<PyIterator>()
+            };
+            match iter.next() {
+                Some(Err(e)) if e.matches(py.get_type::<exc::ValueError>().as_object()) => i,
+                _ => -1
+            }
+        });
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let iter = PyIterator::from_rust_iter(py, items).unwrap();
+        slot.store(iter.as_object().as_ptr() as usize, Ordering::SeqCst);
+        let v: Vec<i32> = iter.as_object().iter_as::<i32>().unwrap().map(|v| v.unwrap()).collect();
+        assert_eq!(vec![0], v);
+    }
+
+    #[test]
+    fn test_from_rust_iter_panic() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let iter = PyIterator::from_rust_iter(py, (0..3).map(|i| {
+            if i == 1 { panic!("iterator panic") }
+            i
+        })).unwrap();
+        let mut iter = iter.as_object().iter_as::<i32>().unwrap();
+        assert_eq!(0, iter.next().unwrap().unwrap());
+        // The panic is reported as SystemError and does not leave the iterator marked as running.
+        assert!(iter.next().unwrap().is_err());
+        assert_eq!(2, iter.next().unwrap().unwrap());
+    }
+}
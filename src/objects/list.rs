@@ -20,6 +20,7 @@ use std;
 use python::{Python, PythonObject, ToPythonPointer};
 use err::{self, PyResult, PyErr};
 use super::object::PyObject;
+use super::tuple::PyTuple;
 use super::exc;
 use ffi::{self, Py_ssize_t};
 use conversion::{ToPyObject, FromPyObject};
@@ -28,15 +29,38 @@ pyobject_newtype!(PyList, PyList_Check, PyList_Type);
 
 impl <'p> PyList<'p> {
     /// Construct a new list with the given elements.
+    ///
+    /// # Panic
+    /// Panics when running out of memory; use `try_new()` to handle that case.
     pub fn new(py: Python<'p>, elements: &[PyObject<'p>]) -> PyList<'p> {
+        PyList::try_new(py, elements).ok().expect("Failed to create list")
+    }
+
+    /// Construct a new list with the given elements.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_new(py: Python<'p>, elements: &[PyObject<'p>]) -> PyResult<'p, PyList<'p>> {
         unsafe {
             let ptr = ffi::PyList_New(elements.len() as Py_ssize_t);
-            let t = err::result_from_owned_ptr(py, ptr).unwrap().unchecked_cast_into::<PyList>();
+            let t = try!(err::result_from_owned_ptr(py, ptr)).unchecked_cast_into::<PyList>();
             for (i, e) in elements.iter().enumerate() {
                 ffi::PyList_SetItem(ptr, i as Py_ssize_t, e.clone().steal_ptr());
             }
-            t
+            Ok(t)
+        }
+    }
+
+    /// Construct a new list from the elements produced by a Rust iterator.
+    /// The elements are converted to python objects one at a time.
+    pub fn from_iter<I, T>(py: Python<'p>, elements: I) -> PyResult<'p, PyList<'p>>
+        where I: IntoIterator<Item=T>, T: ToPyObject<'p>
+    {
+        let list: PyList = unsafe {
+            try!(err::result_cast_from_owned_ptr(py, ffi::PyList_New(0)))
+        };
+        for e in elements {
+            try!(list.append(e));
         }
+        Ok(list)
     }
 
     /// Gets the length of the list.
@@ -58,6 +82,15 @@ impl <'p> PyList<'p> {
         }
     }
 
+    /// Gets the item at the specified index.
+    ///
+    /// Returns `IndexError` if the index is out of range.
+    pub fn try_get_item(&self, index: usize) -> PyResult<'p, PyObject<'p>> {
+        unsafe {
+            err::result_from_borrowed_ptr(self.python(), ffi::PyList_GetItem(self.as_ptr(), index as Py_ssize_t))
+        }
+    }
+
     /// Sets the item at the specified index.
     ///
     /// Panics if the index is out of range.
@@ -66,6 +99,16 @@ impl <'p> PyList<'p> {
         assert!(r == 0);
     }
 
+    /// Sets the item at the specified index.
+    ///
+    /// Returns `IndexError` if the index is out of range.
+    pub fn try_set_item(&self, index: usize, item: PyObject<'p>) -> PyResult<'p, ()> {
+        unsafe {
+            err::error_on_minusone(self.python(),
+                ffi::PyList_SetItem(self.as_ptr(), index as Py_ssize_t, item.steal_ptr()))
+        }
+    }
+
     /// Inserts an item at the specified index.
     ///
     /// Panics if the index is out of range.
@@ -73,6 +116,92 @@ impl <'p> PyList<'p> {
         let r = unsafe { ffi::PyList_Insert(self.as_ptr(), index as Py_ssize_t, item.as_ptr()) };
         assert!(r == 0);
     }
+
+    /// Appends an item to the end of the list.
+    /// This is equivalent to the python expression `self.append(item)`.
+    pub fn append<I>(&self, item: I) -> PyResult<'p, ()> where I: ToPyObject<'p> {
+        let py = self.python();
+        item.with_borrowed_ptr(py, |item| unsafe {
+            err::error_on_minusone(py, ffi::PyList_Append(self.as_ptr(), item))
+        })
+    }
+
+    /// Appends all items from the iterable object to the end of the list.
+    /// This is equivalent to the python expression `self.extend(iterable)`.
+    pub fn extend(&self, iterable: &PyObject<'p>) -> PyResult<'p, ()> {
+        let len = self.len();
+        self.set_slice(len, len, iterable)
+    }
+
+    /// Removes the last item from the list and returns it.
+    ///
+    /// Returns `IndexError` if the list is empty.
+    pub fn pop(&self) -> PyResult<'p, PyObject<'p>> {
+        match self.len() {
+            0 => Err(exc::IndexError::new(self.python(), "pop from empty list")),
+            len => self.pop_at(len - 1)
+        }
+    }
+
+    /// Removes the item at the specified index and returns it.
+    ///
+    /// Returns `IndexError` if the index is out of range.
+    pub fn pop_at(&self, index: usize) -> PyResult<'p, PyObject<'p>> {
+        let item = try!(self.try_get_item(index));
+        try!(self.del_slice(index, index + 1));
+        Ok(item)
+    }
+
+    /// Returns a new list containing the items between `low` and `high`.
+    /// This is equivalent to the python expression `self[low:high]`.
+    pub fn get_slice(&self, low: usize, high: usize) -> PyResult<'p, PyList<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(self.python(),
+                ffi::PyList_GetSlice(self.as_ptr(), low as Py_ssize_t, high as Py_ssize_t))
+        }
+    }
+
+    /// Replaces the items between `low` and `high` with the items from the iterable object.
+    /// This is equivalent to the python expression `self[low:high] = items`.
+    pub fn set_slice(&self, low: usize, high: usize, items: &PyObject<'p>) -> PyResult<'p, ()> {
+        unsafe {
+            err::error_on_minusone(self.python(),
+                ffi::PyList_SetSlice(self.as_ptr(), low as Py_ssize_t, high as Py_ssize_t, items.as_ptr()))
+        }
+    }
+
+    /// Removes the items between `low` and `high`.
+    /// This is equivalent to the python expression `del self[low:high]`.
+    pub fn del_slice(&self, low: usize, high: usize) -> PyResult<'p, ()> {
+        unsafe {
+            err::error_on_minusone(self.python(),
+                ffi::PyList_SetSlice(self.as_ptr(), low as Py_ssize_t, high as Py_ssize_t, std::ptr::null_mut()))
+        }
+    }
+
+    /// Sorts the list in place.
+    /// This is equivalent to the python expression `self.sort()`.
+    pub fn sort(&self) -> PyResult<'p, ()> {
+        unsafe {
+            err::error_on_minusone(self.python(), ffi::PyList_Sort(self.as_ptr()))
+        }
+    }
+
+    /// Reverses the list in place.
+    /// This is equivalent to the python expression `self.reverse()`.
+    pub fn reverse(&self) -> PyResult<'p, ()> {
+        unsafe {
+            err::error_on_minusone(self.python(), ffi::PyList_Reverse(self.as_ptr()))
+        }
+    }
+
+    /// Returns a new tuple containing the items of the list.
+    /// This is equivalent to the python expression `tuple(self)`.
+    pub fn as_tuple(&self) -> PyResult<'p, PyTuple<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(self.python(), ffi::PyList_AsTuple(self.as_ptr()))
+        }
+    }
 }
 
 impl <'p> IntoIterator for PyList<'p> {
@@ -121,6 +250,10 @@ impl <'p> Iterator for PyListIterator<'p> {
 impl <'p, T> ToPyObject<'p> for [T] where T: ToPyObject<'p> {
     type ObjectType = PyList<'p>;
 
+    /// Converts the slice into a new python list.
+    ///
+    /// # Panic
+    /// Panics when running out of memory; use `PyList::from_iter()` to handle that case.
     fn to_py_object(&self, py: Python<'p>) -> PyList<'p> {
         unsafe {
             let ptr = ffi::PyList_New(self.len() as Py_ssize_t);
@@ -140,9 +273,53 @@ impl <'p, T> FromPyObject<'p> for Vec<T> where T: FromPyObject<'p> {
         let list = try!(s.cast_as::<PyList>());
         let mut v = Vec::with_capacity(list.len());
         for i in 0 .. list.len() {
-            v.push(try!(list.get_item(i).extract::<T>()));
+            v.push(try!(list.get_item(i).extract::<T>()
+                .map_err(|e| e.with_context(&format!("item {}", i)))));
         }
         Ok(v)
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::PyList;
+
+    #[test]
+    fn test_append_pop() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::from_iter(py, vec![2, 3]).unwrap();
+        list.append(5).unwrap();
+        assert_eq!(3, list.len());
+        assert_eq!(5, list.pop().unwrap().extract::<i32>().unwrap());
+        assert_eq!(2, list.pop_at(0).unwrap().extract::<i32>().unwrap());
+        assert_eq!(1, list.len());
+        assert!(list.pop_at(1).is_err());
+    }
+
+    #[test]
+    fn test_try_get_item() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = [1, 2].to_py_object(py);
+        assert_eq!(2, list.try_get_item(1).unwrap().extract::<i32>().unwrap());
+        assert!(list.try_get_item(2).is_err());
+    }
+
+    #[test]
+    fn test_slice_sort_reverse() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = [5, 3, 8].to_py_object(py);
+        list.sort().unwrap();
+        assert_eq!(vec![3, 5, 8], list.as_object().extract::<Vec<i32>>().unwrap());
+        list.reverse().unwrap();
+        assert_eq!(vec![8, 5], list.get_slice(0, 2).unwrap().as_object().extract::<Vec<i32>>().unwrap());
+        list.extend([1].to_py_object(py).as_object()).unwrap();
+        list.del_slice(0, 1).unwrap();
+        assert_eq!(3, list.as_tuple().unwrap().len());
+    }
+}
@@ -27,12 +27,14 @@ pub use self::string::PyBytes as PyString;
 #[cfg(feature="python3-sys")]
 pub use self::string::PyUnicode as PyString;
 
-#[cfg(feature="python27-sys")]
-pub use self::iterator::PyIterator;
+pub use self::iterator::{PyIterator, PyTypedIterator};
 pub use self::boolobject::PyBool;
-pub use self::tuple::PyTuple;
+pub use self::tuple::{PyTuple, NoArgs};
 pub use self::dict::PyDict;
 pub use self::list::PyList;
+pub use self::callable::{PyCallable, KeywordArgs, KwArg};
+pub use self::traceback::{PyTraceback, PyTracebackFrames, TracebackFrame};
+pub use self::code::{PyCode, CompileMode, CompileError};
 #[cfg(feature="python27-sys")]
 pub use self::num::PyInt;
 #[cfg(feature="python3-sys")]
@@ -82,10 +84,17 @@ macro_rules! pyobject_newtype(
                 ::std::mem::transmute(obj)
             }
         }
+
+        unsafe impl <'p, 's> ::python::PythonObjectRebind<'p> for $name<'s> {
+            type Output = $name<'p>;
+        }
     );
     ($name: ident, $checkfunction: ident) => (
+        pyobject_newtype!($name, $checkfunction, @name stringify!($name));
+    );
+    ($name: ident, $checkfunction: ident, @name $expected_type_name: expr) => (
         pyobject_newtype!($name);
-        
+
         impl <'p> ::python::PythonObjectWithCheckedDowncast<'p> for $name<'p> {
             #[inline]
             fn downcast_from(obj : ::objects::object::PyObject<'p>) -> Result<$name<'p>, ::python::PythonObjectDowncastError<'p>> {
@@ -93,7 +102,7 @@ macro_rules! pyobject_newtype(
                     if ::ffi::$checkfunction(::python::ToPythonPointer::as_ptr(&obj)) != 0 {
                         Ok($name(obj))
                     } else {
-                        Err(::python::PythonObjectDowncastError(::python::PythonObject::python(&obj)))
+                        Err(::python::PythonObjectDowncastError::new_with_name($expected_type_name, &obj))
                     }
                 }
             }
@@ -104,14 +113,42 @@ macro_rules! pyobject_newtype(
                     if ::ffi::$checkfunction(::python::ToPythonPointer::as_ptr(obj)) != 0 {
                         Ok(::std::mem::transmute(obj))
                     } else {
-                        Err(::python::PythonObjectDowncastError(::python::PythonObject::python(obj)))
+                        Err(::python::PythonObjectDowncastError::new_with_name($expected_type_name, obj))
                     }
                 }
             }
         }
     );
     ($name: ident, $checkfunction: ident, $typeobject: ident) => (
-        pyobject_newtype!($name, $checkfunction);
+        pyobject_newtype!($name);
+
+        impl <'p> ::python::PythonObjectWithCheckedDowncast<'p> for $name<'p> {
+            #[inline]
+            fn downcast_from(obj : ::objects::object::PyObject<'p>) -> Result<$name<'p>, ::python::PythonObjectDowncastError<'p>> {
+                unsafe {
+                    if ::ffi::$checkfunction(::python::ToPythonPointer::as_ptr(&obj)) != 0 {
+                        Ok($name(obj))
+                    } else {
+                        let py = ::python::PythonObject::python(&obj);
+                        Err(::python::PythonObjectDowncastError::new(
+                            <$name as ::python::PythonObjectWithTypeObject>::type_object(py), &obj))
+                    }
+                }
+            }
+            
+            #[inline]
+            fn downcast_borrow_from<'a>(obj : &'a ::objects::object::PyObject<'p>) -> Result<&'a $name<'p>, ::python::PythonObjectDowncastError<'p>> {
+                unsafe {
+                    if ::ffi::$checkfunction(::python::ToPythonPointer::as_ptr(obj)) != 0 {
+                        Ok(::std::mem::transmute(obj))
+                    } else {
+                        let py = ::python::PythonObject::python(obj);
+                        Err(::python::PythonObjectDowncastError::new(
+                            <$name as ::python::PythonObjectWithTypeObject>::type_object(py), obj))
+                    }
+                }
+            }
+        }
         
         impl <'p> ::python::PythonObjectWithTypeObject<'p> for $name<'p> {
             #[inline]
@@ -127,11 +164,13 @@ mod typeobject;
 mod module;
 mod string;
 mod dict;
-#[cfg(feature="python27-sys")]
 mod iterator;
 mod boolobject;
 mod tuple;
 mod list;
+mod callable;
+mod traceback;
+mod code;
 mod num;
 pub mod exc;
 
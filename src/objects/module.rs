@@ -22,7 +22,7 @@ use libc::c_char;
 use python::{Python, PythonObject, ToPythonPointer};
 use conversion::ToPyObject;
 use objects::{PyObject, PyType, PyDict, exc};
-use err::{self, PyResult, PyErr};
+use err::{self, PyResult, PyErr, ToPyErr};
 use std::ffi::{CStr, CString};
 
 pyobject_newtype!(PyModule, PyModule_Check, PyModule_Type);
@@ -30,7 +30,7 @@ pyobject_newtype!(PyModule, PyModule_Check, PyModule_Type);
 impl <'p> PyModule<'p> {
     /// Create a new module object with the __name__ attribute set to name.
     pub fn new(py: Python<'p>, name: &str) -> PyResult<'p, PyModule<'p>> {
-        let name = CString::new(name).unwrap();
+        let name = try!(CString::new(name).map_err(|e| e.to_py_err(py)));
         unsafe {
             err::result_cast_from_owned_ptr(py, ffi::PyModule_New(name.as_ptr()))
         }
@@ -38,7 +38,7 @@ impl <'p> PyModule<'p> {
 
     /// Import the python module with the specified name.
     pub fn import(py: Python<'p>, name: &str) -> PyResult<'p, PyModule<'p>> {
-        let name = CString::new(name).unwrap();
+        let name = try!(CString::new(name).map_err(|e| e.to_py_err(py)));
         unsafe {
             err::result_cast_from_owned_ptr(py, ffi::PyImport_ImportModule(name.as_ptr()))
         }
@@ -67,6 +67,23 @@ impl <'p> PyModule<'p> {
         Ok(module)
     }
 
+    /// Creates a module, initializes it using `init` and inserts it into `sys.modules`,
+    /// so that python code can `import` it like a builtin module.
+    ///
+    /// `init` has the same signature as the initializer passed to `py_module_initializer!`.
+    /// To register a module before the interpreter is initialized,
+    /// use `InterpreterBuilder::builtin_module()`.
+    pub fn register<F>(py: Python<'p>, name: &str, init: F) -> PyResult<'p, PyModule<'p>>
+      where F: FnOnce(Python<'p>, &PyModule<'p>) -> PyResult<'p, ()> {
+        let module = try!(PyModule::new(py, name));
+        try!(init(py, &module));
+        let modules = unsafe {
+            PyObject::from_borrowed_ptr(py, ffi::PyImport_GetModuleDict()).unchecked_cast_into::<PyDict>()
+        };
+        try!(modules.set_item(name, module.as_object()));
+        Ok(module)
+    }
+
     /// Return the dictionary object that implements module‘s namespace;
     /// this object is the same as the __dict__ attribute of the module object.
     pub fn dict(&self) -> PyDict<'p> {
@@ -85,7 +102,7 @@ impl <'p> PyModule<'p> {
             let slice = CStr::from_ptr(ptr).to_bytes();
             match std::str::from_utf8(slice) {
                 Ok(s) => Ok(std::mem::copy_lifetime(self, s)),
-                Err(e) => Err(PyErr::new(try!(exc::UnicodeDecodeError::new_utf8(py, slice, e))))
+                Err(e) => Err(PyErr::from_instance(try!(exc::UnicodeDecodeError::create_utf8(py, slice, e))))
             }
         }
     }
@@ -118,4 +135,25 @@ impl <'p> PyModule<'p> {
     }
 }
 
+#[cfg(test)]
+mod test {
+    use python::Python;
+    use objects::PyModule;
 
+    #[test]
+    fn test_register() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        py.register_module("rust_registered_module", |_py, m| {
+            m.add("answer", 42)
+        }).unwrap();
+        let m = py.import("rust_registered_module").unwrap();
+        assert_eq!(m.get("answer").unwrap().extract::<i32>().unwrap(), 42);
+        assert_eq!(py.eval("__import__('rust_registered_module').answer", None, None)
+                   .unwrap().extract::<i32>().unwrap(), 42);
+        assert!(PyModule::register(py, "rust_failing_module", |py, _m| {
+            Err(py_err!(py, ::objects::exc::ValueError, "init failed"))
+        }).is_err());
+        assert!(py.import("rust_failing_module").is_err());
+    }
+}
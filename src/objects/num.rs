@@ -20,7 +20,7 @@ extern crate num;
 
 use libc::{c_long, c_double};
 use std;
-use python::{Python, PythonObject, ToPythonPointer};
+use python::{Python, PythonObject, PythonObjectDowncastError, ToPythonPointer};
 use err::{self, PyResult, PyErr};
 use super::object::PyObject;
 use super::exc;
@@ -36,12 +36,23 @@ pyobject_newtype!(PyFloat, PyFloat_Check, PyFloat_Type);
 #[cfg(feature="python27-sys")]
 impl <'p> PyInt<'p> {
     /// Creates a new python `int` object.
+    ///
+    /// # Panic
+    /// May panic when running out of memory; use `try_new()` to handle that case.
     pub fn new(py: Python<'p>, val: c_long) -> PyInt<'p> {
         unsafe {
             err::cast_from_owned_ptr_or_panic(py, ffi::PyInt_FromLong(val))
         }
     }
 
+    /// Creates a new python `int` object.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_new(py: Python<'p>, val: c_long) -> PyResult<'p, PyInt<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyInt_FromLong(val))
+        }
+    }
+
     /// Gets the value of this integer.
     pub fn value(&self) -> c_long {
         unsafe { ffi::PyInt_AS_LONG(self.as_ptr()) }
@@ -51,12 +62,23 @@ impl <'p> PyInt<'p> {
 
 impl <'p> PyFloat<'p> {
     /// Creates a new python `float` object.
+    ///
+    /// # Panic
+    /// May panic when running out of memory; use `try_new()` to handle that case.
     pub fn new(py: Python<'p>, val: c_double) -> PyFloat<'p> {
         unsafe {
             err::cast_from_owned_ptr_or_panic(py, ffi::PyFloat_FromDouble(val))
         }
     }
 
+    /// Creates a new python `float` object.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_new(py: Python<'p>, val: c_double) -> PyResult<'p, PyFloat<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyFloat_FromDouble(val))
+        }
+    }
+
     /// Gets the value of this float.
     pub fn value(&self) -> c_double {
         unsafe { ffi::PyFloat_AsDouble(self.as_ptr()) }
@@ -95,11 +117,11 @@ macro_rules! int_fits_c_long(
                 let py = s.python();
                 let val = unsafe { ffi::PyInt_AsLong(s.as_ptr()) };
                 if val == -1 && PyErr::occurred(py) {
-                    return Err(PyErr::fetch(py));
+                    return Err(conversion_error(s, "int"));
                 }
                 match num::traits::cast::<c_long, $rust_type>(val) {
                     Some(v) => Ok(v),
-                    None => Err(overflow_error(py))
+                    None => Err(overflow_error(py, stringify!($rust_type)))
                 }
             }
         }
@@ -110,11 +132,11 @@ macro_rules! int_fits_c_long(
                 let py = s.python();
                 let val = unsafe { ffi::PyLong_AsLong(s.as_ptr()) };
                 if val == -1 && PyErr::occurred(py) {
-                    return Err(PyErr::fetch(py));
+                    return Err(conversion_error(s, "int"));
                 }
                 match num::traits::cast::<c_long, $rust_type>(val) {
                     Some(v) => Ok(v),
-                    None => Err(overflow_error(py))
+                    None => Err(overflow_error(py, stringify!($rust_type)))
                 }
             }
         }
@@ -139,7 +161,7 @@ macro_rules! int_fits_larger_int(
                 let val = try!(s.extract::<$larger_type>());
                 match num::traits::cast::<$larger_type, $rust_type>(val) {
                     Some(v) => Ok(v),
-                    None => Err(overflow_error(py))
+                    None => Err(overflow_error(py, stringify!($rust_type)))
                 }
             }
         }
@@ -219,10 +241,13 @@ impl <'p> FromPyObject<'p> for u64 {
             } else if ffi::PyInt_Check(ptr) != 0 {
                 match num::traits::cast::<c_long, u64>(ffi::PyInt_AS_LONG(ptr)) {
                     Some(v) => Ok(v),
-                    None => Err(overflow_error(py))
+                    None => Err(overflow_error(py, "u64"))
                 }
             } else {
-                let num = try!(err::result_from_owned_ptr(py, ffi::PyNumber_Long(ptr)));
+                let num = match PyObject::from_owned_ptr_opt(py, ffi::PyNumber_Long(ptr)) {
+                    Some(num) => num,
+                    None => return Err(conversion_error(s, "int"))
+                };
                 pylong_as_u64(&num)
             }
         }
@@ -236,7 +261,10 @@ impl <'p> FromPyObject<'p> for u64 {
             if ffi::PyLong_Check(ptr) != 0 {
                 pylong_as_u64(s)
             } else {
-                let num = try!(err::result_from_owned_ptr(py, ffi::PyNumber_Long(ptr)));
+                let num = match PyObject::from_owned_ptr_opt(py, ffi::PyNumber_Long(ptr)) {
+                    Some(num) => num,
+                    None => return Err(conversion_error(s, "int"))
+                };
                 pylong_as_u64(&num)
             }
         }
@@ -256,15 +284,27 @@ impl <'p> FromPyObject<'p> for f64 {
         let py = s.python();
         let v = unsafe { ffi::PyFloat_AsDouble(s.as_ptr()) };
         if v == -1.0 && PyErr::occurred(py) {
-            Err(PyErr::fetch(py))
+            Err(conversion_error(s, "float"))
         } else {
             Ok(v)
         }
     }
 }
 
-fn overflow_error(py: Python) -> PyErr {
-    PyErr::new_lazy_init(py.get_type::<exc::OverflowError>(), None)
+fn overflow_error<'p>(py: Python<'p>, rust_type_name: &str) -> PyErr<'p> {
+    py_err!(py, exc::OverflowError, "value out of range for {}", rust_type_name)
+}
+
+/// Fetches the error raised by a python number conversion function.
+/// A `TypeError` is replaced with a message naming the expected and the received type.
+fn conversion_error<'p>(obj: &PyObject<'p>, expected_type_name: &'static str) -> PyErr<'p> {
+    let py = obj.python();
+    let err = PyErr::fetch(py);
+    if err.matches(py.get_type::<exc::TypeError>().as_object()) {
+        PyErr::from(PythonObjectDowncastError::new_with_name(expected_type_name, obj))
+    } else {
+        err
+    }
 }
 
 impl <'p> ToPyObject<'p> for f32 {
@@ -339,5 +379,13 @@ mod test {
         assert_eq!(v, obj.extract::<u64>().unwrap());
         assert!(obj.extract::<i64>().is_err());
     }
+
+    #[test]
+    fn test_float_try_new() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let f = super::PyFloat::try_new(py, 1.5).unwrap();
+        assert_eq!(1.5, f.value());
+    }
 }
 
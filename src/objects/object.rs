@@ -19,7 +19,7 @@
 use std::mem;
 use libc;
 use ffi;
-use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectWithTypeObject, PythonObjectDowncastError, ToPythonPointer};
+use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectWithTypeObject, PythonObjectDowncastError, PythonObjectRebind, ToPythonPointer};
 use objects::PyType;
 use err::{PyErr, PyResult};
 
@@ -79,6 +79,10 @@ impl <'p> PythonObject<'p> for PyObject<'p> {
     }
 }
 
+unsafe impl <'p, 's> PythonObjectRebind<'p> for PyObject<'s> {
+    type Output = PyObject<'p>;
+}
+
 impl <'p> PythonObjectWithCheckedDowncast<'p> for PyObject<'p> {
     #[inline]
     fn downcast_from(obj: PyObject<'p>) -> Result<PyObject<'p>, PythonObjectDowncastError<'p>> {
@@ -22,7 +22,7 @@ use std::ascii::AsciiExt;
 use std::borrow::Cow;
 use libc::c_char;
 use ffi;
-use python::{Python, PythonObject, ToPythonPointer};
+use python::{Python, PythonObject, PythonObjectDowncastError, ToPythonPointer};
 use super::{exc, PyObject};
 use err::{self, PyResult, PyErr};
 use conversion::{FromPyObject, ToPyObject};
@@ -37,6 +37,9 @@ pub use PyUnicode as PyString;
 
 impl <'p> PyBytes<'p> {
     /// Creates a new python byte string object from the &[u8].
+    ///
+    /// # Panic
+    /// May panic when running out of memory; use `try_new()` to handle that case.
     pub fn new(py: Python<'p>, s: &[u8]) -> PyBytes<'p> {
         let ptr = s.as_ptr() as *const c_char;
         let len = s.len() as ffi::Py_ssize_t;
@@ -46,6 +49,17 @@ impl <'p> PyBytes<'p> {
         }
     }
 
+    /// Creates a new python byte string object from the &[u8].
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_new(py: Python<'p>, s: &[u8]) -> PyResult<'p, PyBytes<'p>> {
+        let ptr = s.as_ptr() as *const c_char;
+        let len = s.len() as ffi::Py_ssize_t;
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                ffi::PyBytes_FromStringAndSize(ptr, len))
+        }
+    }
+
     /// Gets the python string data as byte slice.
     pub fn as_slice(&self) -> &[u8] {
         unsafe {
@@ -70,6 +84,9 @@ impl <'p> PyBytes<'p> {
 
 impl <'p> PyUnicode<'p> {
     /// Creates a new unicode string object from the Rust string.
+    ///
+    /// # Panic
+    /// May panic when running out of memory; use `try_new()` to handle that case.
     pub fn new(py: Python<'p>, s: &str) -> PyUnicode<'p> {
         let ptr = s.as_ptr() as *const c_char;
         let len = s.len() as ffi::Py_ssize_t;
@@ -79,6 +96,17 @@ impl <'p> PyUnicode<'p> {
         }
     }
 
+    /// Creates a new unicode string object from the Rust string.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_new(py: Python<'p>, s: &str) -> PyResult<'p, PyUnicode<'p>> {
+        let ptr = s.as_ptr() as *const c_char;
+        let len = s.len() as ffi::Py_ssize_t;
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                ffi::PyUnicode_FromStringAndSize(ptr, len))
+        }
+    }
+
     /* Note: 'as_slice removed temporarily, we need to reconsider
     // whether we really should expose the platform-dependent Py_UNICODE to user code.
     #[cfg(feature="python27-sys")]
@@ -98,7 +126,7 @@ impl <'p> PyUnicode<'p> {
         };
         match str::from_utf8(bytes.as_slice()) {
             Ok(s) => Ok(Cow::Owned(s.to_owned())),
-            Err(e) => Err(PyErr::new(try!(exc::UnicodeDecodeError::new_utf8(py, bytes.as_slice(), e))))
+            Err(e) => Err(PyErr::from_instance(try!(exc::UnicodeDecodeError::create_utf8(py, bytes.as_slice(), e))))
         }
     }
 
@@ -123,12 +151,12 @@ impl <'p> PyString<'p> {
         if let Ok(s) = o.cast_as::<PyBytes>() {
             match s.to_str() {
                 Ok(s) => Ok(Cow::Borrowed(s)),
-                Err(e) => Err(PyErr::new(try!(exc::UnicodeDecodeError::new_utf8(py, s.as_slice(), e))))
+                Err(e) => Err(PyErr::from_instance(try!(exc::UnicodeDecodeError::create_utf8(py, s.as_slice(), e))))
             }
         } else if let Ok(u) = o.cast_as::<PyUnicode>() {
             u.to_string()
         } else {
-            Err(PyErr::new_lazy_init(py.get_type::<exc::TypeError>(), None))
+            Err(PyErr::from(PythonObjectDowncastError::new_with_name("str or unicode", o)))
         }
     }
 
@@ -140,7 +168,7 @@ impl <'p> PyString<'p> {
         } else if let Ok(u) = o.cast_as::<PyUnicode>() {
             Ok(u.to_string_lossy())
         } else {
-            Err(PyErr::new_lazy_init(py.get_type::<exc::TypeError>(), None))
+            Err(PyErr::from(PythonObjectDowncastError::new_with_name("str or unicode", o)))
         }
     }
 
@@ -150,7 +178,7 @@ impl <'p> PyString<'p> {
         if let Ok(u) = o.cast_as::<PyUnicode>() {
             u.to_string()
         } else {
-            Err(PyErr::new_lazy_init(py.get_type::<exc::TypeError>(), None))
+            Err(PyErr::from(PythonObjectDowncastError::new(py.get_type::<PyUnicode>(), o)))
         }
     }
 
@@ -160,7 +188,7 @@ impl <'p> PyString<'p> {
         if let Ok(u) = o.cast_as::<PyUnicode>() {
             Ok(u.to_string_lossy())
         } else {
-            Err(PyErr::new_lazy_init(py.get_type::<exc::TypeError>(), None))
+            Err(PyErr::from(PythonObjectDowncastError::new(py.get_type::<PyUnicode>(), o)))
         }
     }
 }
@@ -0,0 +1,121 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use python::PythonObject;
+use objects::PyObject;
+use objectprotocol::ObjectProtocol;
+use err::{self, PyResult};
+
+pyobject_newtype!(PyTraceback, PyTraceBack_Check, PyTraceBack_Type);
+
+/// Describes a single entry of a python traceback.
+pub struct TracebackFrame<'p> {
+    /// The name of the file containing the code.
+    pub filename: String,
+    /// The line number that was executing when the exception was raised.
+    pub lineno: usize,
+    /// The name of the function or module.
+    pub name: String,
+    /// The code object that was executing.
+    pub code: PyObject<'p>
+}
+
+impl <'p> PyTraceback<'p> {
+    /// Gets information about the frame of this traceback entry.
+    pub fn frame(&self) -> PyResult<'p, TracebackFrame<'p>> {
+        let tb = self.as_object();
+        let lineno = try!(try!(tb.getattr("tb_lineno")).extract::<usize>());
+        let code = try!(try!(tb.getattr("tb_frame")).getattr("f_code"));
+        let filename = try!(try!(code.getattr("co_filename")).extract::<String>());
+        let name = try!(try!(code.getattr("co_name")).extract::<String>());
+        Ok(TracebackFrame {
+            filename: filename,
+            lineno: lineno,
+            name: name,
+            code: code
+        })
+    }
+
+    /// Gets the next (inner) traceback entry.
+    pub fn next(&self) -> Option<PyTraceback<'p>> {
+        self.as_object().getattr("tb_next").ok()
+            .and_then(|tb| tb.cast_into::<PyTraceback>().ok())
+    }
+
+    /// Returns an iterator over the traceback entries,
+    /// starting with the outermost call.
+    pub fn frames(&self) -> PyTracebackFrames<'p> {
+        PyTracebackFrames { next: Some(self.clone()) }
+    }
+
+    /// Formats the traceback entries like python's `traceback.format_tb()`.
+    pub fn format(&self) -> PyResult<'p, String> {
+        let py = self.python();
+        let traceback = try!(py.import("traceback"));
+        let lines = try!(traceback.as_object().call_method("format_tb", &(self.as_object(),), None));
+        err::join_lines(&lines)
+    }
+}
+
+/// Iterator over the entries of a traceback.
+pub struct PyTracebackFrames<'p> {
+    next: Option<PyTraceback<'p>>
+}
+
+impl <'p> Iterator for PyTracebackFrames<'p> {
+    type Item = PyResult<'p, TracebackFrame<'p>>;
+
+    fn next(&mut self) -> Option<PyResult<'p, TracebackFrame<'p>>> {
+        match self.next.take() {
+            Some(tb) => {
+                self.next = tb.next();
+                Some(tb.frame())
+            }
+            None => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objectprotocol::ObjectProtocol;
+
+    #[cfg(feature="python27-sys")]
+    const BUILTINS: &'static str = "__builtin__";
+    #[cfg(feature="python3-sys")]
+    const BUILTINS: &'static str = "builtins";
+
+    #[test]
+    fn test_frames_and_format() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let builtins = py.import(BUILTINS).unwrap();
+        let mut err = builtins.as_object().call_method("eval", &("1 // 0",), None).unwrap_err();
+        let tb = err.traceback().unwrap();
+        let frames: Vec<_> = tb.frames().map(|f| f.unwrap()).collect();
+        let last = frames.last().unwrap();
+        assert_eq!("<string>", last.filename);
+        assert_eq!(1, last.lineno);
+        assert_eq!("<module>", last.name);
+        assert!(tb.format().unwrap().contains("File \"<string>\", line 1"));
+        let text = err.format().unwrap();
+        assert!(text.starts_with("Traceback (most recent call last):\n"));
+        assert!(text.contains("ZeroDivisionError"));
+    }
+}
@@ -27,22 +27,59 @@ use conversion::{ToPyObject, FromPyObject};
 pyobject_newtype!(PyTuple, PyTuple_Check, PyTuple_Type);
 
 impl <'p> PyTuple<'p> {
+    /// Construct a new tuple with the given elements.
+    ///
+    /// # Panic
+    /// Panics when running out of memory; use `try_new()` to handle that case.
     pub fn new(py: Python<'p>, elements: &[PyObject<'p>]) -> PyTuple<'p> {
+        PyTuple::try_new(py, elements).ok().expect("Failed to create tuple")
+    }
+
+    /// Construct a new tuple with the given elements.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_new(py: Python<'p>, elements: &[PyObject<'p>]) -> PyResult<'p, PyTuple<'p>> {
         unsafe {
             let len = elements.len();
             let ptr = ffi::PyTuple_New(len as Py_ssize_t);
-            let t = err::result_from_owned_ptr(py, ptr).unwrap().unchecked_cast_into::<PyTuple>();
+            let t = try!(err::result_from_owned_ptr(py, ptr)).unchecked_cast_into::<PyTuple>();
             for (i, e) in elements.iter().enumerate() {
                 ffi::PyTuple_SetItem(ptr, i as Py_ssize_t, e.clone().steal_ptr());
             }
-            t
+            Ok(t)
+        }
+    }
+
+    /// Construct a new tuple from the elements produced by a Rust iterator.
+    /// The elements are converted to python objects one at a time.
+    pub fn from_iter<I, T>(py: Python<'p>, elements: I) -> PyResult<'p, PyTuple<'p>>
+        where I: IntoIterator<Item=T>, T: ToPyObject<'p>
+    {
+        // Collect first so that the tuple can be allocated with its exact size.
+        let elements: Vec<PyObject<'p>> = elements.into_iter()
+            .map(|e| e.into_py_object(py).into_object()).collect();
+        unsafe {
+            let ptr = ffi::PyTuple_New(elements.len() as Py_ssize_t);
+            let t = try!(err::result_from_owned_ptr(py, ptr)).unchecked_cast_into::<PyTuple>();
+            for (i, e) in elements.into_iter().enumerate() {
+                ffi::PyTuple_SetItem(ptr, i as Py_ssize_t, e.steal_ptr());
+            }
+            Ok(t)
         }
     }
 
     /// Retrieves the empty tuple.
+    ///
+    /// # Panic
+    /// Panics when running out of memory; use `try_empty()` to handle that case.
     pub fn empty(py: Python<'p>) -> PyTuple<'p> {
+        PyTuple::try_empty(py).ok().expect("Failed to create tuple")
+    }
+
+    /// Retrieves the empty tuple.
+    /// Returns `MemoryError` when running out of memory.
+    pub fn try_empty(py: Python<'p>) -> PyResult<'p, PyTuple<'p>> {
         unsafe {
-            err::result_from_owned_ptr(py, ffi::PyTuple_New(0)).unwrap().unchecked_cast_into::<PyTuple>()
+            err::result_cast_from_owned_ptr(py, ffi::PyTuple_New(0))
         }
     }
 
@@ -64,6 +101,37 @@ impl <'p> PyTuple<'p> {
         }
     }
 
+    /// Gets the item at the specified index.
+    ///
+    /// Returns `IndexError` if the index is out of range.
+    pub fn try_get_item(&self, index: usize) -> PyResult<'p, PyObject<'p>> {
+        unsafe {
+            err::result_from_borrowed_ptr(self.python(), ffi::PyTuple_GetItem(self.as_ptr(), index as Py_ssize_t))
+        }
+    }
+
+    /// Returns a new tuple containing the items between `low` and `high`.
+    /// This is equivalent to the python expression `self[low:high]`.
+    pub fn slice(&self, low: usize, high: usize) -> PyResult<'p, PyTuple<'p>> {
+        unsafe {
+            err::result_cast_from_owned_ptr(self.python(),
+                ffi::PyTuple_GetSlice(self.as_ptr(), low as Py_ssize_t, high as Py_ssize_t))
+        }
+    }
+
+    /// Extracts the argument at the specified index as `T`.
+    ///
+    /// Intended for functions exposed to python with `py_func!`:
+    /// error messages mention the argument name,
+    /// e.g. `argument 'n': expected int, got NoneType`.
+    pub fn extract_arg<T>(&self, index: usize, name: &str) -> PyResult<'p, T> where T: FromPyObject<'p> {
+        let py = self.python();
+        if index >= self.len() {
+            return Err(py_err!(py, exc::TypeError, "missing required argument '{}' (pos {})", name, index + 1));
+        }
+        self.get_item(index).extract::<T>().map_err(|e| e.with_context(&format!("argument '{}'", name)))
+    }
+
     /* Disabled for now; we might want to change the PyObject memory layout for
        compatiblity with Rust 1.0.
     #[inline]
@@ -136,13 +204,12 @@ impl <'p> ExactSizeIterator for PyTupleIterator<'p> {
 
 fn wrong_tuple_length<'p>(t: &PyTuple<'p>, expected_length: usize) -> PyErr<'p> {
     let py = t.python();
-    let msg = format!("Expected tuple of length {}, but got tuple of length {}.", expected_length, t.len());
-    PyErr::new_lazy_init(py.get_type::<exc::ValueError>(), Some(msg.to_py_object(py).into_object()))
+    py_err!(py, exc::ValueError, "expected tuple of length {}, got tuple of length {}", expected_length, t.len())
 }
 
 macro_rules! id (($a:expr) => ($a));
 
-macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+} => (
+macro_rules! tuple_conversion ({$length:expr,$(($n:tt, $T:ident)),+} => (
     impl <'p, $($T: ToPyObject<'p>),+> ToPyObject<'p> for ($($T,)+) {
         type ObjectType = PyTuple<'p>;
 
@@ -159,35 +226,34 @@ macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+
         }
     }
 
-    /* TODO: reimplement this without slice matching
-    impl <'p, 's, $($T: FromPyObject<'p, 's>),+> FromPyObject<'p, 's> for ($($T,)+) {
-        fn from_py_object(s : &'s PyObject<'p>) -> PyResult<'p, ($($T,)+)> {
+    impl <'p, $($T: FromPyObject<'p>),+> FromPyObject<'p> for ($($T,)+) {
+        fn from_py_object(s: &PyObject<'p>) -> PyResult<'p, ($($T,)+)> {
             let t = try!(s.cast_as::<PyTuple>());
-            match t.as_slice() {
-                [$(ref $refN,)+] => Ok((
-                    $(try!($refN.extract::<$T>()),)+
-                )),
-                _ => Err(wrong_tuple_length(t, 2))
+            if t.len() != $length {
+                return Err(wrong_tuple_length(t, $length));
             }
+            Ok((
+                $(try!(t.get_item($n).extract::<$T>()
+                    .map_err(|e| e.with_context(&format!("item {}", $n)))),)+
+            ))
         }
     }
-    */
 ));
 
-tuple_conversion!(1, (ref0, 0, A));
-tuple_conversion!(2, (ref0, 0, A), (ref1, 1, B));
-tuple_conversion!(3, (ref0, 0, A), (ref1, 1, B), (ref2, 2, C));
-tuple_conversion!(4, (ref0, 0, A), (ref1, 1, B), (ref2, 2, C), (ref3, 3, D));
-tuple_conversion!(5, (ref0, 0, A), (ref1, 1, B), (ref2, 2, C), (ref3, 3, D),
-  (ref4, 4, E));
-tuple_conversion!(6, (ref0, 0, A), (ref1, 1, B), (ref2, 2, C), (ref3, 3, D),
-  (ref4, 4, E), (ref5, 5, F));
-tuple_conversion!(7, (ref0, 0, A), (ref1, 1, B), (ref2, 2, C), (ref3, 3, D),
-  (ref4, 4, E), (ref5, 5, F), (ref6, 6, G));
-tuple_conversion!(8, (ref0, 0, A), (ref1, 1, B), (ref2, 2, C), (ref3, 3, D),
-  (ref4, 4, E), (ref5, 5, F), (ref6, 6, G), (ref7, 7, H));
-tuple_conversion!(9, (ref0, 0, A), (ref1, 1, B), (ref2, 2, C), (ref3, 3, D),
-  (ref4, 4, E), (ref5, 5, F), (ref6, 6, G), (ref7, 7, H), (ref8, 8, I));
+tuple_conversion!(1, (0, A));
+tuple_conversion!(2, (0, A), (1, B));
+tuple_conversion!(3, (0, A), (1, B), (2, C));
+tuple_conversion!(4, (0, A), (1, B), (2, C), (3, D));
+tuple_conversion!(5, (0, A), (1, B), (2, C), (3, D),
+  (4, E));
+tuple_conversion!(6, (0, A), (1, B), (2, C), (3, D),
+  (4, E), (5, F));
+tuple_conversion!(7, (0, A), (1, B), (2, C), (3, D),
+  (4, E), (5, F), (6, G));
+tuple_conversion!(8, (0, A), (1, B), (2, C), (3, D),
+  (4, E), (5, F), (6, G), (7, H));
+tuple_conversion!(9, (0, A), (1, B), (2, C), (3, D),
+  (4, E), (5, F), (6, G), (7, H), (8, I));
 
 // Empty tuple:
 
@@ -212,3 +278,55 @@ impl <'p> FromPyObject<'p> for NoArgs {
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::PyTuple;
+
+    #[test]
+    fn test_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let tuple = (1, "a").to_py_object(py);
+        let (a, b) = tuple.as_object().extract::<(i32, String)>().unwrap();
+        assert_eq!(1, a);
+        assert_eq!("a", b);
+        assert!(tuple.as_object().extract::<(i32,)>().is_err());
+        let mut err = tuple.extract_arg::<i32>(1, "n").unwrap_err();
+        let msg = err.instance().to_string();
+        assert!(msg.contains("argument 'n': expected int"), "unexpected message {}", msg);
+    }
+
+    #[test]
+    fn test_from_iter_and_slice() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let tuple = PyTuple::from_iter(py, (1..5).map(|i| i * 2)).unwrap();
+        assert_eq!(4, tuple.len());
+        assert_eq!(6, tuple.try_get_item(2).unwrap().extract::<i32>().unwrap());
+        assert!(tuple.try_get_item(4).is_err());
+        let slice = tuple.slice(1, 3).unwrap();
+        assert_eq!(2, slice.len());
+        assert_eq!(4, slice.get_item(0).extract::<i32>().unwrap());
+    }
+
+    #[test]
+    fn test_try_empty() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert_eq!(0, PyTuple::try_empty(py).unwrap().len());
+    }
+
+    #[test]
+    fn test_from_iter_without_size_hint() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        // filter() reports a lower bound of 0, so the size hint cannot be trusted.
+        let tuple = PyTuple::from_iter(py, (0..10).filter(|i| i % 3 == 0)).unwrap();
+        assert_eq!(4, tuple.len());
+        assert_eq!(9, tuple.get_item(3).extract::<i32>().unwrap());
+        assert_eq!(0, PyTuple::from_iter(py, (0..10).filter(|_| false)).unwrap().len());
+    }
+}
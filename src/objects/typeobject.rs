@@ -21,6 +21,8 @@ use objects::PyObject;
 use ffi;
 use libc::c_char;
 use std;
+use std::borrow::Cow;
+use std::ffi::CStr;
 
 pyobject_newtype!(PyType, PyType_Check, PyType_Type);
 
@@ -38,6 +40,23 @@ impl <'p> PyType<'p> {
         PyObject::from_borrowed_ptr(py, p as *mut ffi::PyObject).unchecked_cast_into::<PyType>()
     }
 
+    /// Gets the name of the type, e.g. `int` or `NoneType`.
+    #[cfg(feature="python27-sys")]
+    pub fn name<'a>(&'a self) -> Cow<'a, str> {
+        unsafe { CStr::from_ptr((*self.as_type_ptr()).tp_name).to_string_lossy() }
+    }
+
+    /// Gets the name of the type, e.g. `int` or `NoneType`.
+    #[cfg(feature="python3-sys")]
+    pub fn name<'a>(&'a self) -> Cow<'a, str> {
+        // The type object layout is opaque in the limited API, so use the __name__ attribute.
+        use objectprotocol::ObjectProtocol;
+        match self.as_object().getattr("__name__").and_then(|name| name.extract::<String>()) {
+            Ok(name) => Cow::Owned(name),
+            Err(_) => Cow::Borrowed("<unknown type>")
+        }
+    }
+
     /// Return true if self is a subtype of b.
     #[inline]
     pub fn is_subtype_of(&self, b : &PyType<'p>) -> bool {
@@ -0,0 +1,194 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::mem;
+use std::marker::PhantomData;
+use ffi;
+use python::{Python, PythonObject, PythonObjectRebind, ToPythonPointer};
+use objects::PyObject;
+use pythonrun::GILGuard;
+use conversion::ToPyObject;
+
+/// An owned reference to a python object that is not tied to the lifetime of the GIL.
+///
+/// `Py<T>` is `'static`, `Send` and `Sync`, so it can be stored in Rust structs,
+/// statics or passed to other threads.
+/// `T` is the object type with `'static` lifetime, e.g. `Py<PyList<'static>>`;
+/// use `as_ref()` or `get()` to access the object while holding the GIL.
+///
+/// Dropping a `Py<T>` acquires the GIL (if necessary) to decrement the reference count.
+/// If the python interpreter was already finalized, the reference is leaked.
+///
+/// A `Py<T>` belongs to the interpreter it was created in.
+/// Accessing it while a different (sub-)interpreter is active panics,
+/// and dropping it there leaks the reference.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, Py, PyList, PythonObject};
+///
+/// let list: Py<PyList<'static>> = {
+///     let gil = Python::acquire_gil();
+///     Py::new(PyList::new(gil.python(), &[]))
+/// };
+/// let gil = Python::acquire_gil();
+/// assert_eq!(0, list.as_ref(gil.python()).len());
+/// ```
+pub struct Py<T> {
+    // Owns one reference to the object.
+    ptr: *mut ffi::PyObject,
+    // The interpreter owning the object.
+    interp: *mut ffi::PyInterpreterState,
+    marker: PhantomData<T>
+}
+
+/// Gets the interpreter of the current thread state. The GIL must be held.
+fn current_interpreter() -> *mut ffi::PyInterpreterState {
+    unsafe { ffi::PyThreadState_GetInterpreter(ffi::PyThreadState_Get()) }
+}
+
+// Python objects may be used from any thread as long as the GIL is held;
+// all access through Py<T> requires a Python token.
+unsafe impl <T> Send for Py<T> {}
+unsafe impl <T> Sync for Py<T> {}
+
+impl <T> Py<T> {
+    /// Creates an owned reference from a python object.
+    pub fn new<'p, O>(obj: O) -> Py<T> where O: PythonObject<'p> + PythonObjectRebind<'static, Output=T> {
+        Py { ptr: obj.steal_ptr(), interp: current_interpreter(), marker: PhantomData }
+    }
+
+    /// Panics if the object belongs to a different interpreter than the current one.
+    #[inline]
+    fn check_interpreter(&self) {
+        assert!(self.interp == current_interpreter(),
+            "Py<T> used in a different interpreter than the one it was created in");
+    }
+
+    /// Borrows the object for the lifetime of the GIL token `py`.
+    #[inline]
+    pub fn as_ref<'a, 'p>(&'a self, _py: Python<'p>) -> &'a <T as PythonObjectRebind<'p>>::Output
+        where T: PythonObjectRebind<'p>
+    {
+        self.check_interpreter();
+        // PyObject and all object types are #[repr(C)] wrappers around the object pointer.
+        unsafe { mem::transmute(&self.ptr) }
+    }
+
+    /// Gets a new reference to the object, bound to the GIL token `py`.
+    #[inline]
+    pub fn get<'p>(&self, py: Python<'p>) -> <T as PythonObjectRebind<'p>>::Output
+        where T: PythonObjectRebind<'p>
+    {
+        self.as_ref(py).clone()
+    }
+
+    /// Converts the owned reference into an object bound to the GIL token `py`.
+    #[inline]
+    pub fn into_object<'p>(self, py: Python<'p>) -> <T as PythonObjectRebind<'p>>::Output
+        where T: PythonObjectRebind<'p>
+    {
+        self.check_interpreter();
+        let ptr = self.ptr;
+        mem::forget(self);
+        unsafe { PythonObject::unchecked_downcast_from(PyObject::from_owned_ptr(py, ptr)) }
+    }
+
+    /// Creates another owned reference to the same object.
+    #[inline]
+    pub fn clone_ref(&self, _py: Python) -> Py<T> {
+        self.check_interpreter();
+        unsafe { ffi::Py_INCREF(self.ptr) };
+        Py { ptr: self.ptr, interp: self.interp, marker: PhantomData }
+    }
+
+    /// Retrieves the underlying FFI pointer (as a borrowed pointer).
+    #[inline]
+    pub fn as_ptr(&self) -> *mut ffi::PyObject {
+        self.ptr
+    }
+}
+
+/// Dropping a `Py<T>` decrements the reference count on the object by 1,
+/// acquiring the GIL if the current thread doesn't hold it.
+///
+/// If the interpreter is not running (in particular after `finalize()`),
+/// the reference is leaked.
+/// If the object belongs to a different interpreter than the current one
+/// (e.g. within `SubInterpreter::run()`), the reference is leaked as well.
+impl <T> Drop for Py<T> {
+    fn drop(&mut self) {
+        if ::subinterpreter::is_entered() {
+            return;
+        }
+        // GILGuard is reentrant, so this also works if the GIL is already held.
+        if let Some(_gil) = GILGuard::acquire_if_running() {
+            if self.interp == current_interpreter() {
+                unsafe { ffi::Py_DECREF(self.ptr) };
+            }
+        }
+    }
+}
+
+impl <'p, T> ToPyObject<'p> for Py<T> where T: PythonObjectRebind<'p> {
+    type ObjectType = <T as PythonObjectRebind<'p>>::Output;
+
+    #[inline]
+    fn to_py_object(&self, py: Python<'p>) -> Self::ObjectType {
+        self.get(py)
+    }
+
+    #[inline]
+    fn into_py_object(self, py: Python<'p>) -> Self::ObjectType {
+        self.into_object(py)
+    }
+
+    #[inline]
+    fn with_borrowed_ptr<F, R>(&self, _py: Python<'p>, f: F) -> R
+      where F: FnOnce(*mut ffi::PyObject) -> R {
+        self.check_interpreter();
+        f(self.ptr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+    use python::{Python, PythonObject};
+    use objects::{PyList, PyObject};
+    use conversion::ToPyObject;
+    use super::Py;
+
+    #[test]
+    fn test_send_between_threads() {
+        let list: Py<PyList<'static>> = {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            Py::new(PyList::new(py, &[1.to_py_object(py).into_object()]))
+        };
+        let obj: Py<PyObject<'static>> = thread::spawn(move || {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            assert_eq!(1, list.as_ref(py).len());
+            Py::new(list.get(py).into_object())
+            // `list` is dropped here while the GIL is held.
+        }).join().unwrap();
+        // `obj` is dropped without holding the GIL.
+        drop(obj);
+    }
+}
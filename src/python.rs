@@ -17,10 +17,13 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 use std::marker::PhantomData;
 use ffi;
-use objects::{PyObject, PyType, PyBool, PyModule};
-use err::PyResult;
+use objects::{PyObject, PyType, PyBool, PyModule, PyDict, PyCode, CompileMode, CompileError};
+use err::{PyResult, ToPyErr};
 use pythonrun::GILGuard;
 
 // Dummy struct representing the global state in the python interpreter.
@@ -72,8 +75,79 @@ pub trait PythonObject<'p> : 'p + Clone + ToPythonPointer {
     }
 }
 
-// Marker type that indicates an error while downcasting
-pub struct PythonObjectDowncastError<'p>(pub Python<'p>);
+/// Error that occurs when a python object could not be downcast to the expected type.
+///
+/// Converting this error into a `PyErr` produces a `TypeError`
+/// with a message like `expected int, got str`.
+pub struct PythonObjectDowncastError<'p> {
+    expected_type: ExpectedType<'p>,
+    received_type: PyType<'p>
+}
+
+enum ExpectedType<'p> {
+    Type(PyType<'p>),
+    Name(&'static str)
+}
+
+impl <'p> PythonObjectDowncastError<'p> {
+    /// Creates a downcast error for an object that is not an instance of `expected_type`.
+    pub fn new(expected_type: PyType<'p>, received: &PyObject<'p>) -> PythonObjectDowncastError<'p> {
+        PythonObjectDowncastError {
+            expected_type: ExpectedType::Type(expected_type),
+            received_type: received.get_type().clone()
+        }
+    }
+
+    /// Creates a downcast error for an object that does not support the expected protocol
+    /// (for example `"iterator"` or `"callable"`).
+    pub fn new_with_name(expected_type_name: &'static str, received: &PyObject<'p>) -> PythonObjectDowncastError<'p> {
+        PythonObjectDowncastError {
+            expected_type: ExpectedType::Name(expected_type_name),
+            received_type: received.get_type().clone()
+        }
+    }
+
+    /// Retrieve python instance from the error.
+    #[inline]
+    pub fn python(&self) -> Python<'p> {
+        self.received_type.python()
+    }
+
+    /// Gets the name of the expected type.
+    pub fn expected_type_name(&self) -> std::borrow::Cow<str> {
+        match self.expected_type {
+            ExpectedType::Type(ref t) => t.name(),
+            ExpectedType::Name(name) => std::borrow::Cow::Borrowed(name)
+        }
+    }
+
+    /// Gets the type of the object that failed to downcast.
+    #[inline]
+    pub fn received_type(&self) -> &PyType<'p> {
+        &self.received_type
+    }
+}
+
+/// RAII type that re-acquires the GIL released by `allow_threads()`,
+/// even if the closure panics.
+struct ReleasedGIL {
+    save: *mut ffi::PyThreadState
+}
+
+impl Drop for ReleasedGIL {
+    fn drop(&mut self) {
+        unsafe { ffi::PyEval_RestoreThread(self.save) }
+    }
+}
+
+/// Maps a python object type to the same type bound to the GIL lifetime `'p`.
+///
+/// This is implemented by all python object types and allows `Py<T>`
+/// to hand out objects with the lifetime of the caller's `Python<'p>` token.
+/// Unsafe because `Output` must have the same memory layout as `Self`.
+pub unsafe trait PythonObjectRebind<'p> {
+    type Output: PythonObject<'p>;
+}
 
 /// Trait implemented by python object types that allow a checked downcast.
 pub trait PythonObjectWithCheckedDowncast<'p> : PythonObject<'p> {
@@ -140,17 +214,47 @@ impl<'p> Python<'p> {
         GILGuard::acquire()
     }
 
-    /// Releases the GIL and allows the use of python on other threads.
+    /// Temporarily releases the GIL, thus allowing other python threads to run.
+    ///
+    /// The GIL is re-acquired when `f` returns, or when it panics.
+    /// The `Send` bounds prevent python objects (and the `Python` token itself)
+    /// from being moved into the closure, where they could be accessed without holding the GIL.
+    /// Use `Py<T>` and re-acquire the GIL within the closure if python access is necessary.
+    ///
+    /// # Example
+    /// ```
+    /// use cpython::Python;
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let sum = gil.python().allow_threads(|| (1..101).fold(0, |a, b| a + b));
+    /// assert_eq!(sum, 5050);
+    /// ```
+    ///
+    /// The GIL guard cannot be used within the closure either:
+    ///
+    /// ```compile_fail
+    /// use cpython::Python;
+    ///
+    /// let gil = Python::acquire_gil();
+    /// gil.python().allow_threads(|| {
+    ///     let py = gil.python();
+    ///     py.None();
+    /// });
+    /// ```
+    pub fn allow_threads<T, F>(self, f: F) -> T where F : Send + FnOnce() -> T, T: Send {
+        unsafe { self.allow_threads_unchecked(f) }
+    }
+
+    /// Temporarily releases the GIL, thus allowing other python threads to run.
+    ///
+    /// Like `allow_threads()`, but without the `Send` bounds on the closure.
     /// Unsafe because we do not ensure that existing references to python objects
     /// are not accessed within the closure.
-    pub unsafe fn allow_threads<T, F>(self, f: F) -> T where F : FnOnce() -> T {
-        // TODO: we should use a type with destructor to be panic-safe, and avoid the unnecessary closure
-        let save = ffi::PyEval_SaveThread();
-        let result = f();
-        ffi::PyEval_RestoreThread(save);
-        result
+    pub unsafe fn allow_threads_unchecked<T, F>(self, f: F) -> T where F : FnOnce() -> T {
+        let _guard = ReleasedGIL { save: ffi::PyEval_SaveThread() };
+        f()
     }
-    
+
     /// Gets the python builtin value `None`.
     #[allow(non_snake_case)] // the python keyword starts with uppercase
     #[inline]
@@ -181,11 +285,173 @@ impl<'p> Python<'p> {
     pub fn import(self, name : &str) -> PyResult<'p, PyModule<'p>> {
         PyModule::import(self, name)
     }
+
+    /// Creates a module using `init` and inserts it into `sys.modules`,
+    /// so that embedded python code can import it. See `PyModule::register()`.
+    pub fn register_module<F>(self, name: &str, init: F) -> PyResult<'p, PyModule<'p>>
+      where F: FnOnce(Python<'p>, &PyModule<'p>) -> PyResult<'p, ()> {
+        PyModule::register(self, name, init)
+    }
+
+    /// Evaluates a python expression and returns its value.
+    ///
+    /// If `globals` is `None`, a new empty namespace is used.
+    /// If `locals` is `None`, it defaults to `globals`.
+    ///
+    /// # Example
+    /// ```
+    /// use cpython::Python;
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let result = py.eval("[i * 2 for i in range(3)]", None, None).unwrap();
+    /// assert_eq!(result.extract::<Vec<i32>>().unwrap(), vec![0, 2, 4]);
+    /// ```
+    pub fn eval(self, code: &str, globals: Option<&PyDict<'p>>, locals: Option<&PyDict<'p>>) -> PyResult<'p, PyObject<'p>> {
+        let code = try!(self.compile(code, "<string>", CompileMode::Eval));
+        code.run(globals, locals)
+    }
+
+    /// Executes one or more python statements.
+    ///
+    /// If `globals` is `None`, a new empty namespace is used.
+    /// If `locals` is `None`, it defaults to `globals`.
+    /// Pass a dictionary as `globals` to retrieve the variables defined by the code.
+    pub fn run(self, code: &str, globals: Option<&PyDict<'p>>, locals: Option<&PyDict<'p>>) -> PyResult<'p, ()> {
+        let code = try!(self.compile(code, "<string>", CompileMode::Exec));
+        code.run(globals, locals).map(|_| ())
+    }
+
+    /// Executes the python script at `path`.
+    ///
+    /// Unless already present, `__file__` is set in `globals` to the script path.
+    /// I/O errors while reading the file are reported as python `IOError`.
+    pub fn run_file<P: AsRef<Path>>(self, path: P, globals: Option<&PyDict<'p>>, locals: Option<&PyDict<'p>>) -> PyResult<'p, ()> {
+        let path = path.as_ref();
+        let mut code = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut code)).map_err(|e| e.to_py_err(self)));
+        let filename = path.to_string_lossy();
+        let new_globals;
+        let globals = match globals {
+            Some(globals) => globals,
+            None => { new_globals = PyDict::new(self); &new_globals }
+        };
+        if globals.get_item("__file__").is_none() {
+            try!(globals.set_item("__file__", &*filename));
+        }
+        let code = try!(self.compile(&code, &filename, CompileMode::Exec));
+        code.run(Some(globals), locals).map(|_| ())
+    }
+
+    /// Compiles python source code into a code object,
+    /// which can be executed repeatedly using `PyCode::run()`.
+    ///
+    /// `filename` is used in tracebacks and syntax errors.
+    ///
+    /// # Example
+    /// ```
+    /// use cpython::{Python, PyDict, CompileMode};
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let code = py.compile("a * b", "<rule>", CompileMode::Eval).unwrap();
+    /// let globals = PyDict::new(py);
+    /// globals.set_item("a", 6).unwrap();
+    /// globals.set_item("b", 7).unwrap();
+    /// assert_eq!(code.run(Some(&globals), None).unwrap().extract::<i32>().unwrap(), 42);
+    /// ```
+    pub fn compile(self, source: &str, filename: &str, mode: CompileMode) -> Result<PyCode<'p>, CompileError<'p>> {
+        PyCode::compile(self, source, filename, mode)
+    }
 }
 
 impl <'p> std::fmt::Debug for PythonObjectDowncastError<'p> {
     fn fmt(&self, f : &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        f.write_str("PythonObjectDowncastError")
+        write!(f, "PythonObjectDowncastError({})", self)
     }
 }
 
+impl <'p> std::fmt::Display for PythonObjectDowncastError<'p> {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "expected {}, got {}", self.expected_type_name(), self.received_type.name())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+    use python::{Python, PythonObject};
+    use owned::Py;
+    use objects::{PyList, PyDict, exc};
+
+    #[test]
+    fn test_allow_threads() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list: Py<PyList<'static>> = Py::new(PyList::new(py, &[]));
+        let len = py.allow_threads(move || {
+            // Another thread can acquire the GIL while it is released.
+            thread::spawn(move || {
+                let gil = Python::acquire_gil();
+                list.as_ref(gil.python()).len()
+            }).join().unwrap()
+        });
+        assert_eq!(len, 0);
+    }
+
+    #[test]
+    fn test_allow_threads_panic() {
+        let result = thread::spawn(|| {
+            let gil = Python::acquire_gil();
+            gil.python().allow_threads(|| panic!("panic while the GIL is released"));
+        }).join();
+        assert!(result.is_err());
+        // The GIL must have been restored and released during unwinding.
+        let gil = Python::acquire_gil();
+        assert_eq!(0, PyList::new(gil.python(), &[]).len());
+    }
+
+    #[test]
+    fn test_eval() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert_eq!(py.eval("1 + 2", None, None).unwrap().extract::<i32>().unwrap(), 3);
+        let locals = PyDict::new(py);
+        locals.set_item("x", 5).unwrap();
+        assert_eq!(py.eval("x * len('ab')", None, Some(&locals)).unwrap().extract::<i32>().unwrap(), 10);
+        let err = py.eval("1 +", None, None).unwrap_err();
+        assert!(err.matches(&py.get_type::<exc::SyntaxError>().into_object()));
+    }
+
+    #[test]
+    fn test_run() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let globals = PyDict::new(py);
+        py.run("def f(a):\n    return a + 1\ny = f(41)", Some(&globals), None).unwrap();
+        assert_eq!(globals.get_item("y").unwrap().extract::<i32>().unwrap(), 42);
+        let err = py.run("raise KeyError('k')", None, None).unwrap_err();
+        assert!(err.matches(&py.get_type::<exc::KeyError>().into_object()));
+    }
+
+    #[test]
+    fn test_run_file() {
+        use std::env;
+        use std::fs::File;
+        use std::io::Write;
+        // Use a unique file name so that concurrent test runs don't interfere.
+        let name = format!("cpython_test_run_file_{}.py", unsafe { ::libc::getpid() });
+        let path = env::temp_dir().join(&name);
+        File::create(&path).unwrap().write_all(b"import os\nname = os.path.basename(__file__)\n").unwrap();
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let globals = PyDict::new(py);
+        let result = py.run_file(&path, Some(&globals), None);
+        let _ = ::std::fs::remove_file(&path);
+        result.unwrap();
+        assert_eq!(globals.get_item("name").unwrap().extract::<String>().unwrap(), name);
+        let err = py.run_file(env::temp_dir().join("cpython_missing_file.py"), None, None).unwrap_err();
+        assert!(err.matches(&py.get_type::<exc::EnvironmentError>().into_object()));
+    }
+}
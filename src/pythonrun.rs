@@ -16,12 +16,24 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::sync::{Once, ONCE_INIT};
+use std::{error, fmt, mem, usize};
+use std::any::{Any, TypeId};
+use std::ffi::CString;
+use std::sync::{Arc, Once, ONCE_INIT};
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
 use ffi;
 use python::Python;
+use objects::PyModule;
+use err::PyResult;
+use interrupt::PyThreadHandle;
 
 static START: Once = ONCE_INIT;
 
+/// Number of GILGuards that are currently alive,
+/// or `FINALIZED` after the interpreter was shut down by `finalize()`.
+static ACTIVE_GUARDS: AtomicUsize = ATOMIC_USIZE_INIT;
+const FINALIZED: usize = usize::MAX / 2;
+
 /// Prepares the use of python in a free-threaded context.
 ///
 /// If the python interpreter is not already initialized, this function
@@ -29,6 +41,8 @@ static START: Once = ONCE_INIT;
 /// (python will not raise the `KeyboardInterrupt` exception).
 /// Python signal handling depends on the notion of a 'main thread', which must be
 /// the thread that initializes the python interpreter.
+///
+/// Use `InterpreterBuilder` to configure the interpreter before it is initialized.
 pub fn prepare_freethreaded_python() {
     // Protect against race conditions when python is not yet initialized
     // and multiple threads concurrently call 'prepare_freethreaded_python()'.
@@ -40,26 +54,514 @@ pub fn prepare_freethreaded_python() {
             // as we can't make the existing python main thread acquire the GIL.
             assert!(ffi::PyEval_ThreadsInitialized() != 0);
         } else {
-            // If python isn't initialized yet, we expect that python threading isn't initialized either.
-            assert!(ffi::PyEval_ThreadsInitialized() == 0);
-            // Initialize python.
-            // We use Py_InitializeEx() with initsigs=0 to disable Python signal handling.
-            // Signal handling depends on the notion of a 'main thread', which doesn't exist in this case.
-            // Note that the 'main thread' notion in python isn't documented properly;
-            // and running python without one is not officially supported.
-            ffi::Py_InitializeEx(0);
-            ffi::PyEval_InitThreads();
-            // PyEval_InitThreads() will acquire the GIL,
-            // but we don't want to hold it at this point
-            // (it's not acquired in the other code paths)
-            // So immediately release the GIL:
-            let _thread_state = ffi::PyEval_SaveThread();
-            // Note that the PyThreadState returned by PyEval_SaveThread is also held in TLS by the python runtime,
-            // and will be restored by PyGILState_Ensure.
+            InterpreterBuilder::new().initialize_python(&InitStrings::default());
         }
     });
 }
 
+/// Error returned by `InterpreterBuilder::initialize()` and `finalize()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InterpreterError {
+    /// The python interpreter was already initialized.
+    AlreadyInitialized,
+    /// The python interpreter is not initialized (or was already finalized).
+    NotInitialized,
+    /// A `GILGuard` is still alive, so the interpreter cannot be finalized.
+    GILInUse,
+    /// A `SubInterpreter` is still alive, so the interpreter cannot be finalized.
+    SubInterpretersAlive,
+    /// A configuration string contained a NUL byte.
+    NulByte
+}
+
+impl fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(error::Error::description(self))
+    }
+}
+
+impl error::Error for InterpreterError {
+    fn description(&self) -> &str {
+        match *self {
+            InterpreterError::AlreadyInitialized => "the python interpreter is already initialized",
+            InterpreterError::NotInitialized => "the python interpreter is not initialized",
+            InterpreterError::GILInUse => "the GIL is still in use",
+            InterpreterError::SubInterpretersAlive => "sub-interpreters are still alive",
+            InterpreterError::NulByte => "string contains a NUL byte"
+        }
+    }
+}
+
+/// Configures and initializes the python interpreter.
+///
+/// The builder must be used before any other use of python
+/// (in particular, before the first `Python::acquire_gil()`),
+/// as the interpreter can only be initialized once.
+///
+/// # Example
+/// ```
+/// use cpython::{InterpreterBuilder, Python};
+///
+/// InterpreterBuilder::new()
+///     .program_name("example")
+///     .argv(&["example", "--verbose"])
+///     .initialize().unwrap();
+/// let gil = Python::acquire_gil();
+/// ```
+#[derive(Debug, Clone)]
+pub struct InterpreterBuilder {
+    program_name: Option<String>,
+    home: Option<String>,
+    path: Option<Vec<String>>,
+    argv: Option<Vec<String>>,
+    install_signal_handlers: bool,
+    builtin_modules: Vec<BuiltinModule>
+}
+
+/// Signature of the module initialization functions registered with `PyImport_AppendInittab()`.
+#[cfg(feature="python27-sys")]
+type ModuleInitFn = extern "C" fn();
+
+/// Signature of the module initialization functions registered with `PyImport_AppendInittab()`.
+#[cfg(feature="python3-sys")]
+type ModuleInitFn = extern "C" fn() -> *mut ffi::PyObject;
+
+/// Initializer of a builtin module, with the signature used by `py_module_initializer!`.
+type ModuleInit = Box<for<'p> Fn(Python<'p>, &PyModule<'p>) -> PyResult<'p, ()> + Send + Sync>;
+
+/// A builtin module added by `InterpreterBuilder::builtin_module()`.
+#[derive(Clone)]
+struct BuiltinModule {
+    name: String,
+    // The type of the initializer; a separate module initialization function
+    // is generated for each type, which uses it to find the initializer.
+    init_type: TypeId,
+    init_fn: ModuleInitFn,
+    init: Arc<ModuleInit>
+}
+
+impl fmt::Debug for BuiltinModule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BuiltinModule({:?})", self.name)
+    }
+}
+
+impl InterpreterBuilder {
+    /// Creates a builder with the default configuration:
+    /// default program name, home and module search path, empty `sys.argv`
+    /// and disabled signal handling.
+    pub fn new() -> InterpreterBuilder {
+        InterpreterBuilder {
+            program_name: None,
+            home: None,
+            path: None,
+            argv: None,
+            install_signal_handlers: false,
+            builtin_modules: Vec::new()
+        }
+    }
+
+    /// Sets the program name (`argv[0]` of the program), which python uses
+    /// to find its run-time libraries. Wraps `Py_SetProgramName()`.
+    pub fn program_name(mut self, name: &str) -> InterpreterBuilder {
+        self.program_name = Some(name.to_owned());
+        self
+    }
+
+    /// Sets the python home directory (the location of the standard libraries).
+    /// Wraps `Py_SetPythonHome()`.
+    pub fn home(mut self, home: &str) -> InterpreterBuilder {
+        self.home = Some(home.to_owned());
+        self
+    }
+
+    /// Sets the module search path (`sys.path`).
+    ///
+    /// On python 3, this wraps `Py_SetPath()` and replaces the path computation.
+    /// On python 2, the default search path is replaced using `PySys_SetPath()`
+    /// after initialization.
+    pub fn path(mut self, path: &[&str]) -> InterpreterBuilder {
+        self.path = Some(path.iter().map(|s| (*s).to_owned()).collect());
+        self
+    }
+
+    /// Sets `sys.argv`. Wraps `PySys_SetArgvEx()`; the script directory is not
+    /// prepended to `sys.path`.
+    pub fn argv(mut self, argv: &[&str]) -> InterpreterBuilder {
+        self.argv = Some(argv.iter().map(|s| (*s).to_owned()).collect());
+        self
+    }
+
+    /// Enables python signal handling (e.g. `KeyboardInterrupt` on SIGINT).
+    ///
+    /// Python signal handlers run on the 'main thread', which is the thread
+    /// that calls `initialize()`.
+    pub fn install_signal_handlers(mut self, install: bool) -> InterpreterBuilder {
+        self.install_signal_handlers = install;
+        self
+    }
+
+    /// Adds a builtin module using `PyImport_AppendInittab()`.
+    ///
+    /// `init` initializes the module when it is first imported; it has the same
+    /// signature as the initializer passed to `py_module_initializer!`.
+    /// This allows an embedding application to expose its API without building a separate
+    /// extension module. After initialization, use `PyModule::register()` instead.
+    ///
+    /// # Panic
+    /// Python identifies builtin modules by a plain function pointer, which is generated
+    /// for each initializer type. Panics if an initializer of the same type
+    /// (e.g. the same function) was already added for another module.
+    ///
+    /// # Example
+    /// ```
+    /// use cpython::{InterpreterBuilder, Python};
+    ///
+    /// InterpreterBuilder::new()
+    ///     .builtin_module("myapp", |_py, m| {
+    ///         try!(m.add("version", "1.0"));
+    ///         Ok(())
+    ///     })
+    ///     .initialize().unwrap();
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let version = py.eval("__import__('myapp').version", None, None).unwrap();
+    /// assert_eq!(version.extract::<String>().unwrap(), "1.0");
+    /// ```
+    pub fn builtin_module<F>(mut self, name: &str, init: F) -> InterpreterBuilder
+        where F: for<'p> Fn(Python<'p>, &PyModule<'p>) -> PyResult<'p, ()>,
+              F: Send + Sync + Any
+    {
+        let init_type = TypeId::of::<F>();
+        assert!(self.builtin_modules.iter().all(|m| m.init_type != init_type),
+            "builtin_module(): the initializer of '{}' is already used for another module", name);
+        self.builtin_modules.push(BuiltinModule {
+            name: name.to_owned(),
+            init_type: init_type,
+            init_fn: builtin_module_init::<F>,
+            init: Arc::new(Box::new(init) as ModuleInit)
+        });
+        self
+    }
+
+    /// Initializes the python interpreter with this configuration.
+    ///
+    /// Fails with `AlreadyInitialized` if python was already initialized,
+    /// either by this crate or by other users of the python C API.
+    pub fn initialize(self) -> Result<(), InterpreterError> {
+        let strings = try!(InitStrings::new(&self));
+        let mut result = Err(InterpreterError::AlreadyInitialized);
+        START.call_once(|| unsafe {
+            if ffi::Py_IsInitialized() != 0 {
+                assert!(ffi::PyEval_ThreadsInitialized() != 0);
+            } else {
+                self.initialize_python(&strings);
+                result = Ok(());
+            }
+        });
+        result
+    }
+
+    unsafe fn initialize_python(&self, strings: &InitStrings) {
+        // If python isn't initialized yet, we expect that python threading isn't initialized either.
+        assert!(ffi::PyEval_ThreadsInitialized() == 0);
+        // Python keeps pointers to the program name and home directory,
+        // so these strings must live until the end of the process.
+        if let Some(ref name) = strings.program_name {
+            ffi::Py_SetProgramName(leak_init_string(name.clone()));
+        }
+        if let Some(ref home) = strings.home {
+            ffi::Py_SetPythonHome(leak_init_string(home.clone()));
+        }
+        // The registry is never freed, as the inittab keeps pointers to the module names.
+        let modules: Vec<RegisteredModule> = self.builtin_modules.iter().zip(strings.builtin_modules.iter())
+            .map(|(module, name)| RegisteredModule::new(module, name.clone())).collect();
+        BUILTIN_MODULES = Box::into_raw(Box::new(modules));
+        for module in &*BUILTIN_MODULES {
+            let result = append_inittab(module.name.as_ptr(), module.init_fn);
+            assert!(result == 0, "PyImport_AppendInittab failed");
+        }
+        if let Some(ref path) = strings.path {
+            set_path_before_init(path);
+        }
+        // By default, we use Py_InitializeEx() with initsigs=0 to disable Python signal handling.
+        // Signal handling depends on the notion of a 'main thread', which doesn't exist in this case.
+        // Note that the 'main thread' notion in python isn't documented properly;
+        // and running python without one is not officially supported.
+        ffi::Py_InitializeEx(if self.install_signal_handlers { 1 } else { 0 });
+        ffi::PyEval_InitThreads();
+        if let Some(ref path) = strings.path {
+            set_path_after_init(path);
+        }
+        if let Some(ref argv) = strings.argv {
+            // PySys_SetArgvEx() copies the arguments into sys.argv.
+            let mut argv = argv.clone();
+            let mut ptrs: Vec<_> = argv.iter_mut().map(|arg| arg.as_mut_ptr()).collect();
+            ffi::PySys_SetArgvEx(ptrs.len() as ::libc::c_int, ptrs.as_mut_ptr(), 0);
+        }
+        // PyEval_InitThreads() will acquire the GIL,
+        // but we don't want to hold it at this point
+        // (it's not acquired in the other code paths)
+        // So immediately release the GIL:
+        let _thread_state = ffi::PyEval_SaveThread();
+        // Note that the PyThreadState returned by PyEval_SaveThread is also held in TLS by the python runtime,
+        // and will be restored by PyGILState_Ensure.
+    }
+}
+
+#[cfg(feature="python27-sys")]
+type InitChar = ::libc::c_char;
+
+#[cfg(feature="python3-sys")]
+type InitChar = ::libc::wchar_t;
+
+/// The builder configuration, converted to NUL-terminated strings in the format
+/// expected by the python initialization functions.
+#[derive(Default)]
+struct InitStrings {
+    program_name: Option<Vec<InitChar>>,
+    home: Option<Vec<InitChar>>,
+    path: Option<Vec<InitChar>>,
+    argv: Option<Vec<Vec<InitChar>>>,
+    builtin_modules: Vec<CString>
+}
+
+#[cfg(windows)]
+const PATH_DELIMITER: &'static str = ";";
+
+#[cfg(not(windows))]
+const PATH_DELIMITER: &'static str = ":";
+
+impl InitStrings {
+    fn new(builder: &InterpreterBuilder) -> Result<InitStrings, InterpreterError> {
+        Ok(InitStrings {
+            program_name: try!(init_string_opt(&builder.program_name)),
+            home: try!(init_string_opt(&builder.home)),
+            path: match builder.path {
+                Some(ref path) => {
+                    let mut joined = String::new();
+                    for (i, entry) in path.iter().enumerate() {
+                        if i > 0 {
+                            joined.push_str(PATH_DELIMITER);
+                        }
+                        joined.push_str(entry);
+                    }
+                    Some(try!(init_string(&joined)))
+                }
+                None => None
+            },
+            argv: match builder.argv {
+                Some(ref argv) => {
+                    let mut result = Vec::with_capacity(argv.len());
+                    for arg in argv {
+                        result.push(try!(init_string(arg)));
+                    }
+                    Some(result)
+                }
+                None => None
+            },
+            builtin_modules: {
+                let mut result = Vec::with_capacity(builder.builtin_modules.len());
+                for module in &builder.builtin_modules {
+                    match CString::new(&module.name[..]) {
+                        Ok(name) => result.push(name),
+                        Err(_) => return Err(InterpreterError::NulByte)
+                    }
+                }
+                result
+            }
+        })
+    }
+}
+
+fn init_string_opt(s: &Option<String>) -> Result<Option<Vec<InitChar>>, InterpreterError> {
+    match *s {
+        Some(ref s) => init_string(s).map(Some),
+        None => Ok(None)
+    }
+}
+
+fn init_string(s: &str) -> Result<Vec<InitChar>, InterpreterError> {
+    if s.contains('\0') {
+        return Err(InterpreterError::NulByte);
+    }
+    let mut result: Vec<InitChar> = encode_init_string(s);
+    result.push(0);
+    Ok(result)
+}
+
+/// The builtin modules added by `initialize()`.
+/// Only accessed by `initialize()` and, with the GIL held, by the module initialization functions.
+static mut BUILTIN_MODULES: *mut Vec<RegisteredModule> = 0 as *mut _;
+
+/// A builtin module that was added to the inittab.
+struct RegisteredModule {
+    name: CString,
+    init_type: TypeId,
+    init_fn: ModuleInitFn,
+    init: Arc<ModuleInit>,
+    #[cfg(feature="python3-sys")]
+    def: ffi::PyModuleDef
+}
+
+impl RegisteredModule {
+    #[cfg(feature="python27-sys")]
+    fn new(module: &BuiltinModule, name: CString) -> RegisteredModule {
+        RegisteredModule {
+            name: name,
+            init_type: module.init_type,
+            init_fn: module.init_fn,
+            init: module.init.clone()
+        }
+    }
+
+    #[cfg(feature="python3-sys")]
+    fn new(module: &BuiltinModule, name: CString) -> RegisteredModule {
+        RegisteredModule {
+            def: ffi::PyModuleDef {
+                m_base: ffi::PyModuleDef_HEAD_INIT,
+                m_name: name.as_ptr(),
+                m_doc: 0 as *const _,
+                m_size: 0, // we don't use per-module state
+                m_methods: 0 as *mut _,
+                m_reload: None,
+                m_traverse: None,
+                m_clear: None,
+                m_free: None
+            },
+            name: name,
+            init_type: module.init_type,
+            init_fn: module.init_fn,
+            init: module.init.clone()
+        }
+    }
+}
+
+/// Finds the registered builtin module with the initializer type `init_type`.
+/// Must be called with the GIL held.
+unsafe fn registered_module(init_type: TypeId) -> &'static mut RegisteredModule {
+    (*BUILTIN_MODULES).iter_mut().find(|m| m.init_type == init_type)
+        .expect("builtin module is not registered")
+}
+
+/// Module initialization function for the builtin module initialized by `F`.
+#[cfg(feature="python27-sys")]
+extern "C" fn builtin_module_init<F: Any>() {
+    let py = unsafe { Python::assume_gil_acquired() };
+    let module = unsafe { registered_module(TypeId::of::<F>()) };
+    let init = module.init.clone();
+    match PyModule::_init(py, &module.name, |py, m| (**init)(py, m)) {
+        Ok(()) => (),
+        Err(e) => e.restore()
+    }
+}
+
+/// Module initialization function for the builtin module initialized by `F`.
+#[cfg(feature="python3-sys")]
+extern "C" fn builtin_module_init<F: Any>() -> *mut ffi::PyObject {
+    use python::ToPythonPointer;
+    let py = unsafe { Python::assume_gil_acquired() };
+    let module = unsafe { registered_module(TypeId::of::<F>()) };
+    let init = module.init.clone();
+    match PyModule::_init(py, &mut module.def, |py, m| (**init)(py, m)) {
+        Ok(m) => m.steal_ptr(),
+        Err(e) => {
+            e.restore();
+            ::std::ptr::null_mut()
+        }
+    }
+}
+
+#[cfg(feature="python27-sys")]
+unsafe fn append_inittab(name: *const ::libc::c_char, init: ModuleInitFn) -> ::libc::c_int {
+    ffi::PyImport_AppendInittab(name, Some(init as unsafe extern "C" fn()))
+}
+
+#[cfg(feature="python3-sys")]
+unsafe fn append_inittab(name: *const ::libc::c_char, init: ModuleInitFn) -> ::libc::c_int {
+    ffi::PyImport_AppendInittab(name, Some(init))
+}
+
+#[cfg(feature="python27-sys")]
+unsafe fn set_path_before_init(_path: &[InitChar]) {}
+
+#[cfg(feature="python27-sys")]
+unsafe fn set_path_after_init(path: &[InitChar]) {
+    // Python 2 has no Py_SetPath(); PySys_SetPath() replaces sys.path with a new list.
+    let mut path = path.to_vec();
+    ffi::PySys_SetPath(path.as_mut_ptr());
+}
+
+#[cfg(feature="python3-sys")]
+unsafe fn set_path_before_init(path: &[InitChar]) {
+    // Py_SetPath() copies the string.
+    ffi::Py_SetPath(path.as_ptr());
+}
+
+#[cfg(feature="python3-sys")]
+unsafe fn set_path_after_init(_path: &[InitChar]) {}
+
+#[cfg(feature="python27-sys")]
+fn encode_init_string(s: &str) -> Vec<InitChar> {
+    s.bytes().map(|b| b as InitChar).collect()
+}
+
+#[cfg(all(feature="python3-sys", windows))]
+fn encode_init_string(s: &str) -> Vec<InitChar> {
+    s.utf16_units().map(|c| c as InitChar).collect()
+}
+
+#[cfg(all(feature="python3-sys", not(windows)))]
+fn encode_init_string(s: &str) -> Vec<InitChar> {
+    s.chars().map(|c| c as InitChar).collect()
+}
+
+fn leak_init_string(mut s: Vec<InitChar>) -> *mut InitChar {
+    let ptr = s.as_mut_ptr();
+    mem::forget(s);
+    ptr
+}
+
+/// Shuts down the python interpreter using `Py_Finalize()`.
+///
+/// Fails with `GILInUse` if any `GILGuard` is still alive (on this or any other thread),
+/// with `SubInterpretersAlive` if any `SubInterpreter` wasn't dropped yet,
+/// and with `NotInitialized` if the interpreter isn't running.
+/// After finalization, the interpreter cannot be used again:
+/// `Python::acquire_gil()` will panic, and dropping a `Py<T>` leaks the reference.
+///
+/// Unsafe because this only guards against uses of the GIL through `GILGuard`:
+/// it must not be called from within python callbacks (e.g. `py_fn!` functions or
+/// extension module methods), which hold the GIL without a guard, and python threads
+/// and other users of the C API must be stopped by the caller.
+pub unsafe fn finalize() -> Result<(), InterpreterError> {
+    if ffi::Py_IsInitialized() == 0 {
+        return Err(InterpreterError::NotInitialized);
+    }
+    // Check the guards first, so that a caller holding the GIL
+    // gets GILInUse regardless of sub-interpreters on other threads.
+    try!(guard_count_error(ACTIVE_GUARDS.load(Ordering::SeqCst)));
+    // Creating a sub-interpreter requires the GIL, so none can be created
+    // once the compare_and_swap below succeeds.
+    if ::subinterpreter::live_count() != 0 {
+        return Err(InterpreterError::SubInterpretersAlive);
+    }
+    try!(guard_count_error(ACTIVE_GUARDS.compare_and_swap(0, FINALIZED, Ordering::SeqCst)));
+    // Py_Finalize() must be called with the GIL held;
+    // the thread state is destroyed by Py_Finalize(), so it must not be released afterwards.
+    let _gstate = ffi::PyGILState_Ensure();
+    ffi::Py_Finalize();
+    Ok(())
+}
+
+fn guard_count_error(count: usize) -> Result<(), InterpreterError> {
+    match count {
+        0 => Ok(()),
+        n if n >= FINALIZED => Err(InterpreterError::NotInitialized),
+        _ => Err(InterpreterError::GILInUse)
+    }
+}
+
 /// RAII type that represents an acquired GIL.
 #[must_use]
 pub struct GILGuard {
@@ -70,10 +572,15 @@ pub struct GILGuard {
 /// by the same thread that acquired it.
 impl !Send for GILGuard {}
 
+/// GILGuard is not Sync, so that a reference to it (from which a
+/// `Python` token can be obtained) cannot be used within `allow_threads()`.
+impl !Sync for GILGuard {}
+
 /// The Drop implementation for GILGuard will release the GIL.
 impl Drop for GILGuard {
     fn drop(&mut self) {
         unsafe { ffi::PyGILState_Release(self.gstate) }
+        ACTIVE_GUARDS.fetch_sub(1, Ordering::SeqCst);
     }
 }
 
@@ -82,15 +589,74 @@ impl GILGuard {
     /// If the python runtime is not already initialized, this function will initialize it.
     /// Note that in this case, the python runtime will not have any main thread, and will
     /// not deliver signals like KeyboardInterrupt.
+    ///
+    /// Panics when called within `SubInterpreter::run()`.
     pub fn acquire() -> GILGuard {
+        if ::subinterpreter::is_entered() {
+            panic!("cannot acquire the GIL of the main interpreter within SubInterpreter::run()");
+        }
         ::pythonrun::prepare_freethreaded_python();
+        if ACTIVE_GUARDS.fetch_add(1, Ordering::SeqCst) >= FINALIZED {
+            ACTIVE_GUARDS.fetch_sub(1, Ordering::SeqCst);
+            panic!("the python interpreter was finalized");
+        }
         let gstate = unsafe { ffi::PyGILState_Ensure() }; // acquire GIL
         GILGuard { gstate: gstate }
     }
 
+    /// Acquires the GIL, unless the python interpreter is not running
+    /// (not initialized yet, or shut down by `finalize()`).
+    /// Used where panicking is not an option, e.g. in destructors.
+    pub fn acquire_if_running() -> Option<GILGuard> {
+        if ACTIVE_GUARDS.fetch_add(1, Ordering::SeqCst) >= FINALIZED
+            || unsafe { ffi::Py_IsInitialized() } == 0 {
+            ACTIVE_GUARDS.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        let gstate = unsafe { ffi::PyGILState_Ensure() };
+        Some(GILGuard { gstate: gstate })
+    }
+
     /// Retrieves the marker type that proves that the GIL was acquired.
     pub fn python<'p>(&'p self) -> Python<'p> {
         unsafe { Python::assume_gil_acquired() }
     }
+
+    /// Gets a handle that identifies the current python thread.
+    ///
+    /// The handle can be sent to other threads to interrupt python code
+    /// running on this thread, see `PyThreadHandle::raise_async()`.
+    pub fn thread_handle(&self) -> PyThreadHandle {
+        PyThreadHandle::current(self.python())
+    }
 }
 
+
+#[cfg(test)]
+mod test {
+    use python::Python;
+    use super::{InterpreterBuilder, InterpreterError, finalize};
+    use objects::PyModule;
+    use err::PyResult;
+
+    #[test]
+    fn test_already_initialized() {
+        let gil = Python::acquire_gil();
+        assert_eq!(InterpreterBuilder::new().program_name("test").initialize(),
+                   Err(InterpreterError::AlreadyInitialized));
+        assert_eq!(InterpreterBuilder::new().argv(&["a\0b"]).initialize(),
+                   Err(InterpreterError::NulByte));
+        assert_eq!(unsafe { finalize() }, Err(InterpreterError::GILInUse));
+        drop(gil);
+    }
+
+    fn init_module<'p>(_py: Python<'p>, _m: &PyModule<'p>) -> PyResult<'p, ()> {
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "already used for another module")]
+    fn test_builtin_module_same_initializer() {
+        InterpreterBuilder::new().builtin_module("a", init_module).builtin_module("b", init_module);
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Support for python sub-interpreters.
+
+use std::ptr;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+use ffi;
+use python::Python;
+use objects::{PyObject, exc};
+use owned::Py;
+use pythonrun::GILGuard;
+use err::PyResult;
+
+/// Number of sub-interpreters that were created and not yet dropped.
+static LIVE_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Returns the number of `SubInterpreter` instances that are alive.
+/// `finalize()` refuses to shut down python while this is nonzero.
+pub fn live_count() -> usize {
+    LIVE_COUNT.load(Ordering::SeqCst)
+}
+
+/// Marker trait for values that do not contain python objects.
+///
+/// This is implemented automatically for all types except `Python`, `GILGuard`,
+/// the python object types and `Py<T>`, and for all types containing them.
+/// `SubInterpreter::run()` uses it to ensure that objects belonging to one
+/// interpreter are not used within another.
+pub unsafe trait Isolated {}
+
+unsafe impl Isolated for .. {}
+
+impl <'p> !Isolated for Python<'p> {}
+impl <'p> !Isolated for PyObject<'p> {}
+impl <T> !Isolated for Py<T> {}
+impl !Isolated for GILGuard {}
+
+/// A python sub-interpreter: an (almost) totally separate environment for the execution
+/// of python code, with its own set of imported modules, `sys` and `builtins`.
+///
+/// The sub-interpreter shares the GIL with the main interpreter.
+/// Use `run()` to execute code within the sub-interpreter.
+/// The sub-interpreter is destroyed using `Py_EndInterpreter()` when the handle is dropped.
+///
+/// Note that extension modules are shared between interpreters,
+/// so sub-interpreters are not a security boundary against malicious native code.
+///
+/// Python objects cached in process-wide statics are shared as well:
+/// for example, the exception types defined by `py_exception!` are created
+/// in whichever interpreter first uses them. Raising such an exception in
+/// another interpreter mixes objects between the interpreters.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, SubInterpreter};
+///
+/// let gil = Python::acquire_gil();
+/// let sub = SubInterpreter::new(gil.python()).unwrap();
+/// drop(gil);
+/// let answer = sub.run(|py| py.eval("6 * 7", None, None).unwrap().extract::<i32>().unwrap());
+/// assert_eq!(answer, 42);
+/// ```
+pub struct SubInterpreter {
+    tstate: *mut ffi::PyThreadState,
+    interp: *mut ffi::PyInterpreterState
+}
+
+/// The sub-interpreter may be entered from any thread; each `run()` uses its own thread state.
+unsafe impl Send for SubInterpreter {}
+
+impl SubInterpreter {
+    /// Creates a new sub-interpreter using `Py_NewInterpreter()`.
+    ///
+    /// The current thread state is restored after the sub-interpreter was created.
+    pub fn new<'p>(py: Python<'p>) -> PyResult<'p, SubInterpreter> {
+        unsafe {
+            let save = ffi::PyThreadState_Get();
+            let tstate = ffi::Py_NewInterpreter();
+            ffi::PyThreadState_Swap(save);
+            if tstate.is_null() {
+                Err(py_err!(py, exc::SystemError, "failed to create sub-interpreter"))
+            } else {
+                LIVE_COUNT.fetch_add(1, Ordering::SeqCst);
+                Ok(SubInterpreter { tstate: tstate, interp: ffi::PyThreadState_GetInterpreter(tstate) })
+            }
+        }
+    }
+
+    /// Acquires the GIL, enters the sub-interpreter and runs `f`.
+    ///
+    /// The `Python` token passed to `f` is bound to the sub-interpreter.
+    /// The `Isolated` bounds ensure that python objects cannot be moved into the closure
+    /// from another interpreter, and that objects created within the sub-interpreter
+    /// cannot escape from it.
+    /// `f` runs in a new thread state of the sub-interpreter, which is destroyed
+    /// and replaced with the previous thread state when `f` returns or panics.
+    ///
+    /// Within `f`, the GIL of the main interpreter cannot be acquired:
+    /// `Python::acquire_gil()` panics, and dropping a `Py<T>` leaks the reference.
+    pub fn run<F, R>(&self, f: F) -> R
+        where F: for<'s> FnOnce(Python<'s>) -> R, F: Isolated, R: Isolated
+    {
+        let _gil = GILGuard::acquire();
+        let _enter = unsafe { ThreadStateSwap::enter(self.interp) };
+        f(unsafe { Python::assume_gil_acquired() })
+    }
+}
+
+impl Drop for SubInterpreter {
+    fn drop(&mut self) {
+        unsafe {
+            // If python was shut down, Py_Finalize() already freed the interpreter.
+            if ffi::Py_IsInitialized() != 0 {
+                let _gil = GILGuard::acquire();
+                let save = ffi::PyThreadState_Swap(self.tstate);
+                // Py_EndInterpreter() leaves the current thread state NULL.
+                ffi::Py_EndInterpreter(self.tstate);
+                ffi::PyThreadState_Swap(save);
+                self.tstate = ptr::null_mut();
+            }
+        }
+        LIVE_COUNT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+thread_local!(static ENTERED: Cell<bool> = Cell::new(false));
+
+/// Returns whether the current thread is running code within `SubInterpreter::run()`.
+pub fn is_entered() -> bool {
+    ENTERED.with(|entered| entered.get())
+}
+
+/// RAII type that makes a new thread state of the interpreter current,
+/// and restores the previous thread state when dropped.
+struct ThreadStateSwap {
+    tstate: *mut ffi::PyThreadState,
+    previous: *mut ffi::PyThreadState,
+    was_entered: bool
+}
+
+impl ThreadStateSwap {
+    unsafe fn enter(interp: *mut ffi::PyInterpreterState) -> ThreadStateSwap {
+        let tstate = ffi::PyThreadState_New(interp);
+        if tstate.is_null() {
+            panic!("failed to create thread state for sub-interpreter");
+        }
+        let was_entered = ENTERED.with(|entered| { let old = entered.get(); entered.set(true); old });
+        ThreadStateSwap {
+            tstate: tstate,
+            previous: ffi::PyThreadState_Swap(tstate),
+            was_entered: was_entered
+        }
+    }
+}
+
+impl Drop for ThreadStateSwap {
+    fn drop(&mut self) {
+        ENTERED.with(|entered| entered.set(self.was_entered));
+        unsafe {
+            ffi::PyThreadState_Clear(self.tstate);
+            ffi::PyThreadState_Swap(self.previous);
+            ffi::PyThreadState_Delete(self.tstate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+    use python::Python;
+    use pythonrun::{finalize, InterpreterError};
+    use super::SubInterpreter;
+
+    #[test]
+    fn test_isolation() {
+        let sub = {
+            let gil = Python::acquire_gil();
+            SubInterpreter::new(gil.python()).unwrap()
+        };
+        sub.run(|py| py.run("import sys\nsys.rust_marker = 1", None, None).unwrap());
+        assert!(sub.run(|py| py.eval("hasattr(__import__('sys'), 'rust_marker')", None, None)
+                             .unwrap().extract::<bool>().unwrap()));
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert!(!py.eval("hasattr(__import__('sys'), 'rust_marker')", None, None)
+                   .unwrap().extract::<bool>().unwrap());
+    }
+
+    #[test]
+    fn test_finalize_refused() {
+        let sub = {
+            let gil = Python::acquire_gil();
+            SubInterpreter::new(gil.python()).unwrap()
+        };
+        assert_eq!(unsafe { finalize() }, Err(InterpreterError::SubInterpretersAlive));
+        drop(sub);
+    }
+
+    #[test]
+    fn test_run_on_other_thread() {
+        let sub = {
+            let gil = Python::acquire_gil();
+            SubInterpreter::new(gil.python()).unwrap()
+        };
+        let (sub, result) = thread::spawn(move || {
+            let result = sub.run(|py| py.eval("len('abc')", None, None).unwrap().extract::<usize>().unwrap());
+            (sub, result)
+        }).join().unwrap();
+        assert_eq!(result, 3);
+        // the thread state of the other thread was destroyed again
+        assert_eq!(sub.run(|py| py.eval("len('abcd')", None, None).unwrap().extract::<usize>().unwrap()), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "SubInterpreter::run")]
+    fn test_acquire_gil_within_run() {
+        let sub = {
+            let gil = Python::acquire_gil();
+            SubInterpreter::new(gil.python()).unwrap()
+        };
+        sub.run(|_py| { let _gil = Python::acquire_gil(); });
+    }
+
+    #[test]
+    #[should_panic(expected = "different interpreter")]
+    fn test_py_from_other_interpreter() {
+        use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+        use python::PythonObject;
+        use objects::{PyList, PyObject};
+        use owned::Py;
+
+        // Statics are not checked by the Isolated bound, so a Py<T> can escape this way.
+        static SLOT: AtomicUsize = ATOMIC_USIZE_INIT;
+        let sub = {
+            let gil = Python::acquire_gil();
+            SubInterpreter::new(gil.python()).unwrap()
+        };
+        sub.run(|py| {
+            let obj: Py<PyObject<'static>> = Py::new(PyList::new(py, &[]).into_object());
+            SLOT.store(Box::into_raw(Box::new(obj)) as usize, Ordering::SeqCst);
+        });
+        let obj = unsafe { Box::from_raw(SLOT.load(Ordering::SeqCst) as *mut Py<PyObject<'static>>) };
+        let gil = Python::acquire_gil();
+        obj.get(gil.python());
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Access to the `sys` module.
+
+use std::io::{self, Write};
+use std::ffi::CString;
+use std::sync::{Arc, Mutex};
+use ffi;
+use python::{Python, PythonObject};
+use conversion::ToPyObject;
+use objects::{PyObject, PyCallable};
+use functionclass::new_function_class;
+use err::{self, PyResult, PyResultExt, ToPyErr};
+
+/// Provides access to the attributes of the `sys` module
+/// using `PySys_GetObject()` and `PySys_SetObject()`.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, PySys};
+///
+/// let gil = Python::acquire_gil();
+/// let sys = PySys::new(gil.python());
+/// let version = sys.get("version").unwrap().unwrap().extract::<String>().unwrap();
+/// sys.set("rust_embedder", "example").unwrap();
+/// ```
+#[derive(Copy, Clone)]
+pub struct PySys<'p> {
+    py: Python<'p>
+}
+
+/// The standard streams that can be redirected using `PySys::redirect()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StdStream {
+    /// `sys.stdout`
+    Stdout,
+    /// `sys.stderr`
+    Stderr
+}
+
+impl StdStream {
+    fn name(self) -> &'static str {
+        match self {
+            StdStream::Stdout => "stdout",
+            StdStream::Stderr => "stderr"
+        }
+    }
+}
+
+impl <'p> PySys<'p> {
+    /// Creates a helper for accessing the `sys` module.
+    pub fn new(py: Python<'p>) -> PySys<'p> {
+        PySys { py: py }
+    }
+
+    /// Gets the attribute `name` of the `sys` module.
+    /// Returns `None` if the attribute does not exist.
+    pub fn get(&self, name: &str) -> PyResult<'p, Option<PyObject<'p>>> {
+        let name = try!(CString::new(name).map_err(|e| e.to_py_err(self.py)));
+        unsafe {
+            // PySys_GetObject() returns a borrowed reference, and does not set an exception.
+            Ok(PyObject::from_borrowed_ptr_opt(self.py, ffi::PySys_GetObject(name.as_ptr() as *mut _)))
+        }
+    }
+
+    /// Sets the attribute `name` of the `sys` module.
+    pub fn set<V>(&self, name: &str, value: V) -> PyResult<'p, ()> where V: ToPyObject<'p> {
+        let name = try!(CString::new(name).map_err(|e| e.to_py_err(self.py)));
+        let py = self.py;
+        value.with_borrowed_ptr(py, |value| unsafe {
+            err::error_on_minusone(py, ffi::PySys_SetObject(name.as_ptr() as *mut _, value))
+        })
+    }
+
+    /// Deletes the attribute `name` of the `sys` module, if it exists.
+    pub fn remove(&self, name: &str) -> PyResult<'p, ()> {
+        let name = try!(CString::new(name).map_err(|e| e.to_py_err(self.py)));
+        unsafe {
+            // PySys_SetObject() with NULL fails if the attribute doesn't exist.
+            if ffi::PySys_GetObject(name.as_ptr() as *mut _).is_null() {
+                return Ok(());
+            }
+            err::error_on_minusone(self.py, ffi::PySys_SetObject(name.as_ptr() as *mut _, ::std::ptr::null_mut()))
+        }
+    }
+
+    /// Replaces `sys.stdout` or `sys.stderr` with a file-like object that writes
+    /// the UTF-8 encoded output into `writer`.
+    ///
+    /// The previous stream is restored when the returned guard is dropped.
+    pub fn redirect<W>(&self, stream: StdStream, writer: W) -> PyResult<'p, RedirectGuard<'p>>
+        where W: Write + Send + 'static
+    {
+        let write_writer = Arc::new(Mutex::new(writer));
+        let flush_writer = write_writer.clone();
+        // A panic in the writer poisons the mutex; keep using the writer afterwards,
+        // like unsynchronized writes from python would.
+        let file = try!(new_stream_object(self.py,
+            move |s| write_writer.lock().unwrap_or_else(|e| e.into_inner()).write_all(s.as_bytes()),
+            move || flush_writer.lock().unwrap_or_else(|e| e.into_inner()).flush()));
+        self.replace_stream(stream, file)
+    }
+
+    /// Replaces `sys.stdout` or `sys.stderr` with a file-like object
+    /// that passes each written string to `callback`.
+    ///
+    /// The previous stream is restored when the returned guard is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use cpython::{Python, PySys, StdStream};
+    ///
+    /// let output = Arc::new(Mutex::new(String::new()));
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// {
+    ///     let output = output.clone();
+    ///     let _guard = PySys::new(py).redirect_to_callback(StdStream::Stdout,
+    ///         move |s| output.lock().unwrap().push_str(s)).unwrap();
+    ///     py.run("print('hello')", None, None).unwrap();
+    /// }
+    /// assert_eq!(*output.lock().unwrap(), "hello\n");
+    /// ```
+    pub fn redirect_to_callback<F>(&self, stream: StdStream, callback: F) -> PyResult<'p, RedirectGuard<'p>>
+        where F: Fn(&str) + Send + Sync + 'static
+    {
+        let file = try!(new_stream_object(self.py,
+            move |s| { callback(s); Ok(()) },
+            || Ok(())));
+        self.replace_stream(stream, file)
+    }
+
+    fn replace_stream(&self, stream: StdStream, file: PyObject<'p>) -> PyResult<'p, RedirectGuard<'p>> {
+        let previous = try!(self.get(stream.name()));
+        try!(self.set(stream.name(), file));
+        Ok(RedirectGuard { sys: *self, stream: stream, previous: previous })
+    }
+}
+
+/// Restores the previous `sys.stdout` or `sys.stderr` when dropped.
+#[must_use]
+pub struct RedirectGuard<'p> {
+    sys: PySys<'p>,
+    stream: StdStream,
+    previous: Option<PyObject<'p>>
+}
+
+impl <'p> Drop for RedirectGuard<'p> {
+    fn drop(&mut self) {
+        let result = match self.previous.take() {
+            Some(previous) => self.sys.set(self.stream.name(), previous),
+            None => self.sys.remove(self.stream.name())
+        };
+        if let Err(e) = result {
+            e.print();
+        }
+    }
+}
+
+/// Creates a file-like object with `write()` and `flush()` methods that call the given closures.
+/// I/O errors are raised as python `IOError`, and panics as `SystemError`
+/// (by `PyCallable::from_closure()`).
+fn new_stream_object<'p, W, F>(py: Python<'p>, write: W, flush: F) -> PyResult<'p, PyObject<'p>>
+    where W: Fn(&str) -> io::Result<()> + Send + Sync + 'static,
+          F: Fn() -> io::Result<()> + Send + Sync + 'static
+{
+    let write_fn = try!(PyCallable::from_closure(py, "write", move |py, args, _kwargs| {
+        let s = try!(args.extract_arg::<String>(0, "s"));
+        try!(write(&s).py_err(py));
+        Ok(s.chars().count().to_py_object(py).into_object())
+    }));
+    let flush_fn = try!(PyCallable::from_closure(py, "flush", move |py, _args, _kwargs| {
+        try!(flush().py_err(py));
+        Ok(py.None())
+    }));
+    let isatty_fn = try!(PyCallable::from_closure(py, "isatty", |py, _args, _kwargs| {
+        Ok(py.False().into_object())
+    }));
+    let file = try!(new_function_class(py, "RustStream",
+        vec![("write", write_fn), ("flush", flush_fn), ("isatty", isatty_fn)]));
+    try!(::objectprotocol::ObjectProtocol::setattr(&file, "encoding", "utf-8"));
+    Ok(file)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use python::{Python, PythonObject, ToPythonPointer};
+    use objects::exc;
+    use super::{PySys, StdStream};
+
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_get_set() {
+        let gil = Python::acquire_gil();
+        let sys = PySys::new(gil.python());
+        assert!(sys.get("path").unwrap().is_some());
+        assert!(sys.get("rust_no_such_attribute").unwrap().is_none());
+        sys.set("rust_test_attribute", 5).unwrap();
+        assert_eq!(sys.get("rust_test_attribute").unwrap().unwrap().extract::<i32>().unwrap(), 5);
+        sys.remove("rust_test_attribute").unwrap();
+        assert!(sys.get("rust_test_attribute").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_redirect() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let sys = PySys::new(py);
+        let previous = sys.get("stderr").unwrap();
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        {
+            let _guard = sys.redirect(StdStream::Stderr, SharedBuffer(buffer.clone())).unwrap();
+            py.run("import sys\nsys.stderr.write('error: ')\nsys.stderr.write(u'\\xe4')\nsys.stderr.flush()", None, None).unwrap();
+        }
+        assert_eq!(&buffer.lock().unwrap()[..], "error: \u{e4}".as_bytes());
+        assert_eq!(sys.get("stderr").unwrap().map(|s| s.as_ptr()), previous.map(|s| s.as_ptr()));
+    }
+
+    #[test]
+    fn test_redirect_panic() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let _guard = PySys::new(py).redirect_to_callback(StdStream::Stdout, |_s| panic!("callback failed")).unwrap();
+        let err = py.run("import sys\nsys.stdout.write('x')", None, None).unwrap_err();
+        assert!(err.matches(py.get_type::<exc::SystemError>().as_object()));
+    }
+
+    struct PanicOnce(bool, Arc<Mutex<Vec<u8>>>);
+
+    impl Write for PanicOnce {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if !self.0 {
+                self.0 = true;
+                panic!("first write failed");
+            }
+            self.1.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_redirect_after_panic() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let _guard = PySys::new(py).redirect(StdStream::Stdout, PanicOnce(false, buffer.clone())).unwrap();
+        assert!(py.run("import sys\nsys.stdout.write('x')", None, None).is_err());
+        // The writer's mutex is poisoned now, but the stream keeps working.
+        py.run("import sys\nsys.stdout.write('y')", None, None).unwrap();
+        assert_eq!(*buffer.lock().unwrap(), b"y");
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Control over python's warning filters.
+
+use python::{Python, PythonObject};
+use objects::{PyObject, PyType, PyDict, PyList, PyModule, NoArgs};
+use objectprotocol::ObjectProtocol;
+use err::PyResult;
+
+/// The action taken by a warning filter for matching warnings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WarningAction {
+    /// Turn matching warnings into exceptions.
+    Error,
+    /// Never print matching warnings.
+    Ignore,
+    /// Always print matching warnings.
+    Always,
+    /// Print the first occurrence of matching warnings for each location.
+    Default,
+    /// Print the first occurrence of matching warnings for each module.
+    Module,
+    /// Print only the first occurrence of matching warnings.
+    Once
+}
+
+impl WarningAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            WarningAction::Error => "error",
+            WarningAction::Ignore => "ignore",
+            WarningAction::Always => "always",
+            WarningAction::Default => "default",
+            WarningAction::Module => "module",
+            WarningAction::Once => "once"
+        }
+    }
+}
+
+/// A warning that was recorded by `CatchWarnings`.
+pub struct WarningMessage<'p> {
+    /// The warning category.
+    pub category: PyType<'p>,
+    /// The warning message.
+    pub message: String,
+    /// The file name of the location the warning is attributed to.
+    pub filename: String,
+    /// The line number of the location the warning is attributed to.
+    pub lineno: usize
+}
+
+/// Saves the state of the warning filters and restores it when dropped,
+/// like python's `with warnings.catch_warnings():` block.
+///
+/// Filters installed while the guard is alive are discarded when it is dropped.
+/// If created with `record` set, emitted warnings are collected
+/// instead of being printed and can be retrieved with `recorded()`.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, PyErr, PythonObject, CatchWarnings, WarningAction};
+/// use cpython::exc::DeprecationWarning;
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let guard = CatchWarnings::new(py, true).unwrap();
+/// guard.simple_filter(WarningAction::Always, None).unwrap();
+/// PyErr::warn(py, py.get_type::<DeprecationWarning>().as_object(), "old api", 1).unwrap();
+/// assert_eq!("old api", guard.recorded().unwrap()[0].message);
+/// ```
+pub struct CatchWarnings<'p> {
+    warnings: PyModule<'p>,
+    manager: PyObject<'p>,
+    log: Option<PyList<'p>>
+}
+
+impl <'p> CatchWarnings<'p> {
+    /// Saves the current warning filters.
+    /// If `record` is true, emitted warnings are recorded instead of printed.
+    pub fn new(py: Python<'p>, record: bool) -> PyResult<'p, CatchWarnings<'p>> {
+        let warnings = try!(py.import("warnings"));
+        let kwargs = PyDict::new(py);
+        try!(kwargs.set_item("record", record));
+        let manager = try!(try!(warnings.as_object().getattr("catch_warnings")).call(&NoArgs, Some(&kwargs)));
+        let log = try!(manager.call_method("__enter__", &NoArgs, None));
+        let log = if record { Some(try!(log.cast_into::<PyList>())) } else { None };
+        Ok(CatchWarnings { warnings: warnings, manager: manager, log: log })
+    }
+
+    /// Inserts a filter for all warnings of the given category
+    /// (all warnings if `None`), like `warnings.simplefilter()`.
+    pub fn simple_filter(&self, action: WarningAction, category: Option<&PyType<'p>>) -> PyResult<'p, ()> {
+        let py = self.manager.python();
+        let category = match category {
+            Some(category) => category.as_object().clone(),
+            None => py.get_type::<::objects::exc::Warning>().into_object()
+        };
+        try!(self.warnings.as_object().call_method("simplefilter", &(action.as_str(), category), None));
+        Ok(())
+    }
+
+    /// Inserts a filter for warnings of the given category whose message
+    /// matches the regular expression `message`, like `warnings.filterwarnings()`.
+    pub fn filter(&self, action: WarningAction, message: &str, category: &PyType<'p>) -> PyResult<'p, ()> {
+        try!(self.warnings.as_object().call_method("filterwarnings",
+            &(action.as_str(), message, category.as_object()), None));
+        Ok(())
+    }
+
+    /// Gets the warnings recorded so far.
+    /// Returns an empty vector if the guard was created without `record`.
+    pub fn recorded(&self) -> PyResult<'p, Vec<WarningMessage<'p>>> {
+        let mut messages = Vec::new();
+        if let Some(ref log) = self.log {
+            for item in log.clone() {
+                let message = try!(try!(item.getattr("message")).str());
+                messages.push(WarningMessage {
+                    category: try!(try!(item.getattr("category")).cast_into::<PyType>()),
+                    message: ::objects::PyString::extract_lossy(&message).map(|s| s.into_owned()).unwrap_or(String::new()),
+                    filename: try!(try!(item.getattr("filename")).extract::<String>()),
+                    lineno: try!(try!(item.getattr("lineno")).extract::<usize>())
+                });
+            }
+        }
+        Ok(messages)
+    }
+}
+
+impl <'p> Drop for CatchWarnings<'p> {
+    fn drop(&mut self) {
+        let py = self.manager.python();
+        let none = py.None();
+        if let Err(e) = self.manager.call_method("__exit__", &(none.clone(), none.clone(), none), None) {
+            e.print();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objects::exc;
+    use err::PyErr;
+    use super::{CatchWarnings, WarningAction};
+
+    #[test]
+    fn test_catch_warnings() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let category = py.get_type::<exc::UserWarning>();
+        {
+            let guard = CatchWarnings::new(py, true).unwrap();
+            guard.simple_filter(WarningAction::Always, None).unwrap();
+            PyErr::warn(py, category.as_object(), "first", 1).unwrap();
+            PyErr::warn_explicit(py, category.as_object(), "second", "test.py", 42, Some("test")).unwrap();
+            let recorded = guard.recorded().unwrap();
+            assert_eq!(2, recorded.len());
+            assert_eq!("second", recorded[1].message);
+            assert_eq!("test.py", recorded[1].filename);
+            assert_eq!(42, recorded[1].lineno);
+            assert!(recorded[1].category == category);
+        }
+        {
+            let guard = CatchWarnings::new(py, false).unwrap();
+            guard.filter(WarningAction::Error, "^fatal", &category).unwrap();
+            assert!(PyErr::warn(py, category.as_object(), "fatal problem", 1).is_err());
+            assert!(PyErr::warn(py, category.as_object(), "nul\0byte", 1).is_err());
+        }
+    }
+}